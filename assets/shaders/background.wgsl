@@ -0,0 +1,53 @@
+// Animated background for BackgroundPlane, see src/background.rs.
+// Only uses WebGL2-friendly features: one uniform block, constant loop bounds.
+#import bevy_pbr::mesh_vertex_output MeshVertexOutput
+#import bevy_pbr::mesh_view_bindings globals
+
+struct BackgroundMaterial {
+    primary: vec4<f32>,
+    secondary: vec4<f32>,
+    background: vec4<f32>,
+    // x: pattern (0 gradient, 1 stripes, 2 parallax), y: speed, z: pattern scale, w: aspect ratio
+    params: vec4<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> material: BackgroundMaterial;
+
+fn stripes(uv: vec2<f32>, scale: f32, offset: f32) -> f32 {
+    let v = fract((uv.x + uv.y) * scale + offset);
+    return smoothstep(0.0, 0.05, v) - smoothstep(0.45, 0.5, v);
+}
+
+fn dots(uv: vec2<f32>, scale: f32, radius: f32) -> f32 {
+    let cell = fract(uv * scale) - vec2<f32>(0.5);
+    return 1.0 - smoothstep(radius * 0.8, radius, length(cell));
+}
+
+@fragment
+fn fragment(in: MeshVertexOutput) -> @location(0) vec4<f32> {
+    let t = globals.time * material.params.y;
+    let pattern = i32(material.params.x + 0.5);
+    let scale = material.params.z;
+    // the plane is rotated so that uv.x runs vertically on screen and uv.y horizontally;
+    // swap them back, and keep pattern cells square regardless of the window shape
+    let uv = vec2<f32>(in.uv.y * material.params.w, in.uv.x);
+
+    // slow vertical gradient that drifts over time
+    let wave = 0.5 + 0.5 * sin(in.uv.x * 3.0 + t * 0.5);
+    var color = mix(material.background, material.primary, wave * 0.35);
+
+    if pattern == 1 {
+        color = mix(color, material.secondary, stripes(uv, scale, t * 0.1) * 0.15);
+    } else if pattern == 2 {
+        // far layers are smaller, dimmer and slower
+        for (var i = 0; i < 3; i = i + 1) {
+            let depth = f32(i + 1);
+            let offset = vec2<f32>(t * 0.05 / depth, t * 0.02 / depth);
+            let layer = dots(uv + offset + vec2<f32>(depth * 0.37), scale * depth, 0.2);
+            color = mix(color, material.secondary, layer * 0.25 / depth);
+        }
+    }
+
+    return vec4<f32>(color.rgb, 1.0);
+}
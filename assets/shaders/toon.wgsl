@@ -0,0 +1,45 @@
+// Banded diffuse and rim light for ToonMaterial, see src/toon.rs.
+#import bevy_pbr::mesh_vertex_output MeshVertexOutput
+#import bevy_pbr::mesh_view_bindings view, lights
+
+struct ToonMaterial {
+    base_color: vec4<f32>,
+    rim_color: vec4<f32>,
+    bands: f32,
+    rim_power: f32,
+    rim_strength: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> material: ToonMaterial;
+@group(1) @binding(1)
+var base_color_texture: texture_2d<f32>;
+@group(1) @binding(2)
+var base_color_sampler: sampler;
+
+@fragment
+fn fragment(in: MeshVertexOutput) -> @location(0) vec4<f32> {
+    var base_color = material.base_color;
+#ifdef VERTEX_UVS
+    base_color = base_color * textureSample(base_color_texture, base_color_sampler, in.uv);
+#endif
+
+    let normal = normalize(in.world_normal);
+    let view_direction = normalize(view.world_position - in.world_position.xyz);
+    let bands = max(material.bands, 1.0);
+
+    var light = lights.ambient_color.rgb;
+    for (var i = 0u; i < lights.n_directional_lights; i = i + 1u) {
+        let directional = lights.directional_lights[i];
+        let n_dot_l = max(dot(normal, directional.direction_to_light), 0.0);
+        // the lit side snaps to `bands` flat steps
+        let banded = ceil(n_dot_l * bands) / bands;
+        // divided like lambertian diffuse, so it is as bright as StandardMaterial
+        light = light + directional.color.rgb * banded / 3.14159265;
+    }
+
+    let facing = max(dot(normal, view_direction), 0.0);
+    let rim = pow(1.0 - facing, material.rim_power) * material.rim_strength;
+
+    return vec4<f32>(base_color.rgb * light + material.rim_color.rgb * rim, base_color.a);
+}
@@ -0,0 +1,42 @@
+// Inverted hull outline for OutlineMaterial, see src/toon.rs. The mesh is pushed out along
+// its normals and drawn with front faces culled, so only a rim around it is visible.
+#import bevy_pbr::mesh_functions as mesh_functions
+#import bevy_pbr::skinning
+#import bevy_pbr::mesh_bindings mesh
+#import bevy_pbr::mesh_view_bindings view
+
+struct OutlineMaterial {
+    color: vec4<f32>,
+    width: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> material: OutlineMaterial;
+
+struct Vertex {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+#ifdef SKINNED
+    @location(5) joint_indices: vec4<u32>,
+    @location(6) joint_weights: vec4<f32>,
+#endif
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> @builtin(position) vec4<f32> {
+#ifdef SKINNED
+    let model = bevy_pbr::skinning::skin_model(vertex.joint_indices, vertex.joint_weights);
+    let normal = bevy_pbr::skinning::skin_normals(model, vertex.normal);
+#else
+    let model = mesh.model;
+    let normal = mesh_functions::mesh_normal_local_to_world(vertex.normal);
+#endif
+    let world_position = model * vec4<f32>(vertex.position, 1.0);
+    let extruded = world_position.xyz + normalize(normal) * material.width;
+    return view.view_proj * vec4<f32>(extruded, 1.0);
+}
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return material.color;
+}
@@ -0,0 +1,597 @@
+use bevy::{
+    asset::LoadState,
+    ecs::{query::Has, system::Command},
+    gltf::Gltf,
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::HashMap,
+};
+use std::{fmt, time::Duration};
+
+use crate::scene_ready::{SceneInstanceReady, SceneReadySet};
+
+pub struct AnimationLibraryPlugin;
+
+impl Plugin for AnimationLibraryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<AnimationLibrary>()
+            .add_event::<AnimationTrigger>()
+            .add_systems(
+                Update,
+                (
+                    resolve_animation_libraries,
+                    link_animation_players.in_set(SceneReadySet::PostProcess),
+                    drive_animation_state_machines,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The animation clips of a glTF file, by name.
+///
+/// Created empty from a `Gltf` handle, and filled in once the `Gltf` has loaded.
+/// Characters that play clips from it get a `Handle<AnimationLibrary>` on their scene root.
+/// If the `Gltf` fails to load, or lacks a required clip, an error is logged and the
+/// characters don't play those clips.
+#[derive(TypeUuid, TypePath)]
+#[uuid = "0d3c7a4e-5b8f-4a61-9f2e-7c1b6d2e8a43"]
+pub struct AnimationLibrary {
+    gltf: Handle<Gltf>,
+    clips: HashMap<String, Handle<AnimationClip>>,
+    required: Vec<String>,
+    resolved: bool,
+    failed: bool,
+}
+
+impl AnimationLibrary {
+    pub fn new(gltf: Handle<Gltf>) -> Self {
+        Self {
+            gltf,
+            clips: HashMap::default(),
+            required: Vec::new(),
+            resolved: false,
+            failed: false,
+        }
+    }
+
+    /// Clips that are reported when the `Gltf` doesn't have them.
+    pub fn require<'a>(mut self, names: impl IntoIterator<Item = &'a str>) -> Self {
+        self.required.extend(names.into_iter().map(str::to_owned));
+        self
+    }
+
+    /// True once the clips have been read from the `Gltf`.
+    pub fn is_resolved(&self) -> bool {
+        self.resolved
+    }
+
+    /// True once the clips have been read, or the `Gltf` failed to load.
+    pub fn is_settled(&self) -> bool {
+        self.resolved || self.failed
+    }
+
+    pub fn clip(&self, name: &str) -> Result<Handle<AnimationClip>, MissingAnimation> {
+        self.clips
+            .get(name)
+            .cloned()
+            .ok_or_else(|| MissingAnimation {
+                name: name.to_owned(),
+                available: self.names(),
+            })
+    }
+
+    /// Clip names, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.clips.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn missing(&self) -> Vec<MissingAnimation> {
+        self.required
+            .iter()
+            .filter_map(|name| self.clip(name).err())
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct MissingAnimation {
+    pub name: String,
+    pub available: Vec<String>,
+}
+
+impl fmt::Display for MissingAnimation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "no animation named {:?}, available: {}",
+            self.name,
+            self.available.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for MissingAnimation {}
+
+/// On the scene root of a character: the `AnimationPlayer`s inside its instantiated scene.
+/// Inserted on `SceneInstanceReady`.
+#[derive(Component)]
+pub struct AnimationTarget(pub Vec<Entity>);
+
+/// On an `AnimationPlayer`: the scene root it belongs to.
+#[derive(Component)]
+pub struct AnimationPlayerOf(pub Entity);
+
+/// Plays a clip by name on the `AnimationPlayer`s of a character's scene.
+///
+/// `entity` is the scene root holding the `Handle<AnimationLibrary>`.
+/// Logs an error if the library or the clip is missing.
+pub struct PlayAnimation {
+    pub entity: Entity,
+    pub name: String,
+    pub repeat: bool,
+    pub speed: f32,
+    /// Crossfade from the current clip, instant if zero.
+    pub transition: Duration,
+}
+
+impl PlayAnimation {
+    pub fn new(entity: Entity, name: &str) -> Self {
+        Self {
+            entity,
+            name: name.to_owned(),
+            repeat: false,
+            speed: 1.0,
+            transition: Duration::ZERO,
+        }
+    }
+
+    pub fn repeat(mut self) -> Self {
+        self.repeat = true;
+        self
+    }
+
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn transition(mut self, transition: Duration) -> Self {
+        self.transition = transition;
+        self
+    }
+}
+
+impl Command for PlayAnimation {
+    fn apply(self, world: &mut World) {
+        let Some(library_handle) = world.get::<Handle<AnimationLibrary>>(self.entity) else {
+            error!("{:?} has no animation library", self.entity);
+            return;
+        };
+        let Some(library) = world
+            .resource::<Assets<AnimationLibrary>>()
+            .get(library_handle)
+        else {
+            error!("animation library of {:?} is not loaded", self.entity);
+            return;
+        };
+        let clip = match library.clip(&self.name) {
+            Ok(clip) => clip,
+            Err(err) => {
+                error!("{:?}: {}", self.entity, err);
+                return;
+            }
+        };
+
+        let Some(target) = world.get::<AnimationTarget>(self.entity) else {
+            warn!("{:?} has no animation player yet", self.entity);
+            return;
+        };
+        for player_entity in target.0.clone() {
+            let Some(mut player) = world.get_mut::<AnimationPlayer>(player_entity) else {
+                continue;
+            };
+            if self.transition.is_zero() {
+                player.play(clip.clone());
+            } else {
+                player.play_with_transition(clip.clone(), self.transition);
+            }
+            player.set_speed(self.speed);
+            if self.repeat {
+                player.repeat();
+            } else {
+                player.stop_repeating();
+            }
+        }
+    }
+}
+
+/// Links every ready scene to the `AnimationPlayer`s nested in its glTF hierarchy,
+/// so each character can be controlled separately.
+fn link_animation_players(
+    mut commands: Commands,
+    mut ready: EventReader<SceneInstanceReady>,
+    q_children: Query<&Children>,
+    q_player: Query<(), With<AnimationPlayer>>,
+) {
+    for SceneInstanceReady { root } in ready.iter() {
+        let root = *root;
+        let players: Vec<Entity> = q_children
+            .iter_descendants(root)
+            .filter(|entity| q_player.contains(*entity))
+            .collect();
+        for player in players.iter() {
+            commands.entity(*player).insert(AnimationPlayerOf(root));
+        }
+        commands.entity(root).insert(AnimationTarget(players));
+    }
+}
+
+fn resolve_animation_libraries(
+    mut gltf_events: EventReader<AssetEvent<Gltf>>,
+    asset_server: Res<AssetServer>,
+    gltfs: Res<Assets<Gltf>>,
+    mut libraries: ResMut<Assets<AnimationLibrary>>,
+) {
+    let mut loaded = false;
+    for event in gltf_events.iter() {
+        if let AssetEvent::Created { .. } | AssetEvent::Modified { .. } = event {
+            loaded = true;
+        }
+    }
+
+    // libraries added after their gltf finished loading are picked up as well
+    let pending = libraries
+        .iter()
+        .any(|(_, library)| !library.resolved && !library.failed);
+    if !loaded && !pending {
+        return;
+    }
+
+    for (_, library) in libraries.iter_mut() {
+        let Some(gltf) = gltfs.get(&library.gltf) else {
+            if !library.failed && asset_server.get_load_state(&library.gltf) == LoadState::Failed {
+                error!(
+                    "failed to load {:?}, its animations won't play",
+                    asset_server.get_handle_path(&library.gltf)
+                );
+                library.failed = true;
+            }
+            continue;
+        };
+        library.clips = gltf.named_animations.clone();
+        library.resolved = true;
+        for missing in library.missing() {
+            error!(
+                "{:?}: {}",
+                asset_server.get_handle_path(&library.gltf),
+                missing
+            );
+        }
+    }
+}
+
+/// States of the character animation state machine, each mapped to a clip of the panda.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Reflect)]
+pub enum CharacterAnimation {
+    #[default]
+    Idle,
+    StartRun,
+    Running,
+    Emote,
+}
+
+impl CharacterAnimation {
+    pub fn clip_name(&self) -> &'static str {
+        match self {
+            CharacterAnimation::Idle => "idle.001",
+            CharacterAnimation::StartRun => "start_run",
+            CharacterAnimation::Running => "running",
+            CharacterAnimation::Emote => "hehu",
+        }
+    }
+
+    pub fn clip_names() -> impl Iterator<Item = &'static str> {
+        [
+            CharacterAnimation::Idle,
+            CharacterAnimation::StartRun,
+            CharacterAnimation::Running,
+            CharacterAnimation::Emote,
+        ]
+        .into_iter()
+        .map(|state| state.clip_name())
+    }
+
+    /// One-shot clips play once and then leave the state on their own.
+    pub fn is_looping(&self) -> bool {
+        match self {
+            CharacterAnimation::Idle | CharacterAnimation::Running => true,
+            CharacterAnimation::StartRun | CharacterAnimation::Emote => false,
+        }
+    }
+}
+
+/// Forces a character into a state, e.g. to play an emote.
+#[derive(Event)]
+pub struct AnimationTrigger {
+    pub entity: Entity,
+    pub state: CharacterAnimation,
+}
+
+/// Picks the character's clip from its parameters and plays it with a crossfade.
+///
+/// Needs a `Handle<AnimationLibrary>` on the same entity. The machine itself only
+/// does bookkeeping, so it can be stepped without a world.
+#[derive(Component, Reflect)]
+pub struct AnimationStateMachine {
+    /// Parameter: whether the character wants to run.
+    pub running: bool,
+    pub crossfade: Duration,
+    state: CharacterAnimation,
+    elapsed: f32,
+    requested: Option<CharacterAnimation>,
+    started: bool,
+}
+
+impl Default for AnimationStateMachine {
+    fn default() -> Self {
+        Self {
+            running: false,
+            crossfade: Duration::from_millis(200),
+            state: CharacterAnimation::default(),
+            elapsed: 0.0,
+            requested: None,
+            started: false,
+        }
+    }
+}
+
+impl AnimationStateMachine {
+    pub fn state(&self) -> CharacterAnimation {
+        self.state
+    }
+
+    /// Seconds spent in the current state.
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Switches to `state` on the next step, regardless of the parameters.
+    pub fn trigger(&mut self, state: CharacterAnimation) {
+        self.requested = Some(state);
+    }
+
+    /// The state a one-shot clip returns to, or the parameters lead to.
+    fn resting_state(&self) -> CharacterAnimation {
+        if self.running {
+            CharacterAnimation::Running
+        } else {
+            CharacterAnimation::Idle
+        }
+    }
+
+    fn next_state(&self, clip_duration: Option<f32>) -> CharacterAnimation {
+        if let Some(requested) = self.requested {
+            return requested;
+        }
+
+        let finished = clip_duration.map_or(false, |duration| self.elapsed >= duration);
+        match self.state {
+            CharacterAnimation::Idle if self.running => CharacterAnimation::StartRun,
+            CharacterAnimation::StartRun if !self.running => CharacterAnimation::Idle,
+            CharacterAnimation::Running if !self.running => CharacterAnimation::Idle,
+            state if !state.is_looping() && finished => self.resting_state(),
+            state => state,
+        }
+    }
+
+    /// Advances time by `delta` seconds and returns the state that was entered, if any.
+    /// `clip_duration` is the length of the current state's clip, once known.
+    pub fn step(&mut self, delta: f32, clip_duration: Option<f32>) -> Option<CharacterAnimation> {
+        self.elapsed += delta;
+
+        let next = self.next_state(clip_duration);
+        self.requested = None;
+        if next == self.state && self.started {
+            return None;
+        }
+
+        self.state = next;
+        self.elapsed = 0.0;
+        self.started = true;
+        Some(next)
+    }
+}
+
+fn drive_animation_state_machines(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut triggers: EventReader<AnimationTrigger>,
+    mut q_machine: Query<(
+        Entity,
+        &mut AnimationStateMachine,
+        &Handle<AnimationLibrary>,
+        Has<AnimationTarget>,
+    )>,
+    libraries: Res<Assets<AnimationLibrary>>,
+    clips: Res<Assets<AnimationClip>>,
+) {
+    for trigger in triggers.iter() {
+        if let Ok((_, mut machine, _, _)) = q_machine.get_mut(trigger.entity) {
+            machine.trigger(trigger.state);
+        }
+    }
+
+    for (entity, mut machine, library_handle, has_target) in q_machine.iter_mut() {
+        // until the scene is ready, triggers stay queued on the machine
+        if !has_target {
+            continue;
+        }
+        let Some(library) = libraries
+            .get(library_handle)
+            .filter(|library| library.is_resolved())
+        else {
+            continue;
+        };
+        let clip_duration = library
+            .clip(machine.state().clip_name())
+            .ok()
+            .and_then(|clip| clips.get(&clip))
+            .map(|clip| clip.duration());
+
+        if let Some(state) = machine.step(time.delta_seconds(), clip_duration) {
+            let mut play = PlayAnimation::new(entity, state.clip_name());
+            if state.is_looping() {
+                play = play.repeat();
+            }
+            commands.add(play.transition(machine.crossfade));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::HandleId;
+
+    fn clip_handle() -> Handle<AnimationClip> {
+        Handle::weak(HandleId::random::<AnimationClip>())
+    }
+
+    #[test]
+    fn links_players_of_every_ready_scene() {
+        let mut app = App::new();
+        app.add_event::<SceneInstanceReady>()
+            .add_systems(Update, link_animation_players);
+
+        // root > Armature > player, and a second player further down
+        let mut players = vec![];
+        let root = app
+            .world
+            .spawn_empty()
+            .with_children(|root| {
+                root.spawn(Name::new("Armature")).with_children(|armature| {
+                    let mut player = armature.spawn(AnimationPlayer::default());
+                    player.with_children(|player| {
+                        players.push(player.spawn(AnimationPlayer::default()).id());
+                    });
+                    players.push(player.id());
+                });
+            })
+            .id();
+        let other = app.world.spawn_empty().id();
+        let unready = app
+            .world
+            .spawn_empty()
+            .with_children(|root| {
+                root.spawn(AnimationPlayer::default());
+            })
+            .id();
+
+        app.world.send_event(SceneInstanceReady { root });
+        app.world.send_event(SceneInstanceReady { root: other });
+        app.update();
+
+        let mut linked = app.world.get::<AnimationTarget>(root).unwrap().0.clone();
+        linked.sort();
+        players.sort();
+        assert_eq!(linked, players);
+        for player in players {
+            assert_eq!(app.world.get::<AnimationPlayerOf>(player).unwrap().0, root);
+        }
+        assert!(app
+            .world
+            .get::<AnimationTarget>(other)
+            .unwrap()
+            .0
+            .is_empty());
+        assert!(app.world.get::<AnimationTarget>(unready).is_none());
+    }
+
+    #[test]
+    fn library_finds_clips_by_name() {
+        let idle = clip_handle();
+        let running = clip_handle();
+        let mut library = AnimationLibrary::new(Handle::default()).require(["running", "hehu"]);
+        library.clips.insert("running".to_owned(), running.clone());
+        library.clips.insert("idle.001".to_owned(), idle.clone());
+
+        assert_eq!(library.clip("idle.001").unwrap(), idle);
+        assert_eq!(library.clip("running").unwrap(), running);
+
+        let err = library.clip("walk").unwrap_err();
+        assert_eq!(err.name, "walk");
+        assert_eq!(err.available, vec!["idle.001", "running"]);
+        assert_eq!(
+            err.to_string(),
+            "no animation named \"walk\", available: idle.001, running"
+        );
+
+        let missing: Vec<String> = library.missing().into_iter().map(|m| m.name).collect();
+        assert_eq!(missing, vec!["hehu"]);
+    }
+
+    /// A machine that has entered its initial `Idle` state.
+    fn started_machine() -> AnimationStateMachine {
+        let mut machine = AnimationStateMachine::default();
+        assert_eq!(machine.step(0.0, None), Some(CharacterAnimation::Idle));
+        machine
+    }
+
+    #[test]
+    fn idle_starts_running_then_runs() {
+        let mut machine = started_machine();
+        machine.running = true;
+
+        assert_eq!(
+            machine.step(0.1, Some(2.0)),
+            Some(CharacterAnimation::StartRun)
+        );
+        assert_eq!(machine.step(0.5, Some(1.0)), None);
+        assert_eq!(machine.state(), CharacterAnimation::StartRun);
+        assert_eq!(
+            machine.step(0.6, Some(1.0)),
+            Some(CharacterAnimation::Running)
+        );
+        // looping clips don't finish
+        assert_eq!(machine.step(5.0, Some(1.0)), None);
+    }
+
+    #[test]
+    fn running_stops_to_idle() {
+        let mut machine = started_machine();
+        machine.running = true;
+        machine.step(0.1, None);
+        machine.step(1.0, Some(1.0));
+        assert_eq!(machine.state(), CharacterAnimation::Running);
+
+        machine.running = false;
+        assert_eq!(machine.step(0.1, Some(1.0)), Some(CharacterAnimation::Idle));
+        assert_eq!(machine.elapsed(), 0.0);
+    }
+
+    #[test]
+    fn emote_returns_to_resting_state_after_clip() {
+        let mut machine = started_machine();
+        machine.trigger(CharacterAnimation::Emote);
+        assert_eq!(
+            machine.step(0.1, Some(3.0)),
+            Some(CharacterAnimation::Emote)
+        );
+
+        // the duration passed in is the emote clip's from here on
+        assert_eq!(machine.step(1.0, Some(1.5)), None);
+        assert_eq!(machine.step(0.4, None), None);
+        assert_eq!(machine.step(0.1, Some(1.5)), Some(CharacterAnimation::Idle));
+
+        machine.running = true;
+        machine.trigger(CharacterAnimation::Emote);
+        machine.step(0.1, None);
+        assert_eq!(
+            machine.step(1.5, Some(1.5)),
+            Some(CharacterAnimation::Running)
+        );
+    }
+}
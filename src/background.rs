@@ -0,0 +1,187 @@
+use bevy::{
+    asset::LoadState,
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    render::render_resource::{AsBindGroup, ShaderRef},
+};
+
+use crate::{
+    camera::VisibleWorldRect,
+    theme::Palette,
+    utils::AssetHandle,
+    warmup::{FailedShaders, WarmupMaterialPlugin},
+    BackgroundPlane,
+};
+
+pub static BACKGROUND_SHADER_PATH: &str = "shaders/background.wgsl";
+
+/// Draws `BackgroundPlane` with `BackgroundMaterial`, or with its flat `StandardMaterial`
+/// when `BackgroundStyle::Flat` is chosen or the shader failed to load or compile.
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MaterialPlugin::<BackgroundMaterial>::default(),
+            WarmupMaterialPlugin::<BackgroundMaterial>::default(),
+        ))
+        .init_resource::<BackgroundSettings>()
+        .add_systems(Update, (apply_background_style, update_background_material));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BackgroundStyle {
+    /// The plain unlit plane.
+    Flat,
+    #[default]
+    Gradient,
+    Stripes,
+    /// Dots scrolling at different speeds.
+    Parallax,
+}
+
+impl BackgroundStyle {
+    fn pattern(&self) -> f32 {
+        match self {
+            BackgroundStyle::Flat | BackgroundStyle::Gradient => 0.0,
+            BackgroundStyle::Stripes => 1.0,
+            BackgroundStyle::Parallax => 2.0,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy)]
+pub struct BackgroundSettings {
+    pub style: BackgroundStyle,
+    /// Multiplies the animation speed; 0 stops it.
+    pub speed: f32,
+    /// Pattern repetitions over the height of the screen.
+    pub scale: f32,
+}
+
+impl Default for BackgroundSettings {
+    fn default() -> Self {
+        Self {
+            style: BackgroundStyle::default(),
+            speed: 1.0,
+            scale: 8.0,
+        }
+    }
+}
+
+#[derive(AsBindGroup, TypeUuid, TypePath, Clone)]
+#[uuid = "6b1f3c52-2a7e-4d8b-9c41-5e0f7a3d2b19"]
+pub struct BackgroundMaterial {
+    #[uniform(0)]
+    pub primary: Color,
+    #[uniform(0)]
+    pub secondary: Color,
+    #[uniform(0)]
+    pub background: Color,
+    /// Pattern, speed, scale and aspect ratio, see the shader.
+    #[uniform(0)]
+    pub params: Vec4,
+}
+
+impl BackgroundMaterial {
+    pub fn new(palette: &Palette, settings: &BackgroundSettings) -> Self {
+        Self {
+            primary: palette.primary,
+            secondary: palette.secondary,
+            background: palette.background,
+            params: Vec4::new(
+                settings.style.pattern(),
+                settings.speed,
+                settings.scale,
+                1.0,
+            ),
+        }
+    }
+}
+
+impl Material for BackgroundMaterial {
+    fn fragment_shader() -> ShaderRef {
+        BACKGROUND_SHADER_PATH.into()
+    }
+}
+
+fn use_flat_background(
+    settings: &BackgroundSettings,
+    shader: Option<&AssetHandle<BackgroundPlane, Shader>>,
+    asset_server: &AssetServer,
+    failed_shaders: &FailedShaders,
+) -> bool {
+    let shader_failed = shader.map_or(true, |shader| {
+        asset_server.get_load_state(&shader.handle) == LoadState::Failed
+            || failed_shaders.contains(&shader.handle)
+    });
+    settings.style == BackgroundStyle::Flat || shader_failed
+}
+
+fn apply_background_style(
+    mut commands: Commands,
+    settings: Res<BackgroundSettings>,
+    asset_server: Res<AssetServer>,
+    failed_shaders: Res<FailedShaders>,
+    shader: Option<Res<AssetHandle<BackgroundPlane, Shader>>>,
+    flat_material: Option<Res<AssetHandle<BackgroundPlane, StandardMaterial>>>,
+    background_material: Option<Res<AssetHandle<BackgroundPlane, BackgroundMaterial>>>,
+    q_plane: Query<
+        (
+            Entity,
+            Option<&Handle<StandardMaterial>>,
+            Option<&Handle<BackgroundMaterial>>,
+        ),
+        With<BackgroundPlane>,
+    >,
+) {
+    let (Some(flat_material), Some(background_material)) = (flat_material, background_material)
+    else {
+        return;
+    };
+    let flat = use_flat_background(&settings, shader.as_deref(), &asset_server, &failed_shaders);
+
+    // an entity with both handles would be drawn twice
+    for (entity, flat_handle, animated_handle) in q_plane.iter() {
+        if flat && flat_handle.is_none() {
+            commands
+                .entity(entity)
+                .remove::<Handle<BackgroundMaterial>>()
+                .insert(flat_material.handle.clone());
+        } else if !flat && animated_handle.is_none() {
+            commands
+                .entity(entity)
+                .remove::<Handle<StandardMaterial>>()
+                .insert(background_material.handle.clone());
+        }
+    }
+}
+
+fn update_background_material(
+    palette: Res<Palette>,
+    settings: Res<BackgroundSettings>,
+    world_rect: Res<VisibleWorldRect>,
+    background_material: Option<Res<AssetHandle<BackgroundPlane, BackgroundMaterial>>>,
+    mut background_materials: ResMut<Assets<BackgroundMaterial>>,
+) {
+    let Some(background_material) = background_material else {
+        return;
+    };
+    if !palette.is_changed()
+        && !settings.is_changed()
+        && !world_rect.is_changed()
+        && !background_material.is_added()
+    {
+        return;
+    }
+
+    if let Some(material) = background_materials.get_mut(&background_material.handle) {
+        let size = world_rect.rect.size();
+        *material = BackgroundMaterial::new(&palette, &settings);
+        if size.y > 0.0 {
+            // width over height, the shader scales its horizontal axis by it
+            material.params.w = size.x / size.y;
+        }
+    }
+}
@@ -1,71 +1,181 @@
-use bevy::{prelude::*, ecs::system::EntityCommands};
+use bevy::{ecs::system::EntityCommands, prelude::*};
 
-use crate::PrimaryColorHue;
+use crate::{
+    focus::{Focusable, Focused},
+    theme::UiTheme,
+};
 
-fn button_background_color(hue: f32) -> Color {
-    Color::hsl(hue * 360.0, 0.5, 0.4)
+/// Dims a button and its widget; see `UiTheme::button_disabled`.
+#[derive(Component)]
+pub struct ButtonDisabled;
+
+/// Like `ButtonDisabled`, but removed again once the timer finishes.
+/// Used to ignore accidental clicks right after a screen appears.
+#[derive(Component)]
+pub struct ButtonCooldown(pub Timer);
+
+impl ButtonCooldown {
+    pub fn from_secs(secs: f32) -> Self {
+        Self(Timer::from_seconds(secs, TimerMode::Once))
+    }
 }
 
-fn button_hover_color(hue: f32) -> Color {
-    Color::hsl(hue * 360.0, 0.5, 0.45)
+/// Buttons that can't be activated right now.
+pub type InactiveButton = Or<(With<ButtonDisabled>, With<ButtonCooldown>)>;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ButtonSize {
+    Small,
+    Medium,
+    #[default]
+    Large,
+}
+
+impl ButtonSize {
+    fn width(&self) -> f32 {
+        match self {
+            ButtonSize::Small => 120.0,
+            ButtonSize::Medium => 160.0,
+            ButtonSize::Large => 200.0,
+        }
+    }
+
+    fn height(&self) -> f32 {
+        match self {
+            ButtonSize::Small => 50.0,
+            ButtonSize::Medium => 70.0,
+            ButtonSize::Large => 100.0,
+        }
+    }
+
+    fn font_size(&self) -> f32 {
+        match self {
+            ButtonSize::Small => 24.0,
+            ButtonSize::Medium => 30.0,
+            ButtonSize::Large => 36.0,
+        }
+    }
+}
+
+pub fn button_color(
+    theme: &UiTheme,
+    interaction: Interaction,
+    focused: bool,
+    disabled: bool,
+) -> Color {
+    if disabled {
+        return theme.button_disabled;
+    }
+
+    // the focused button is highlighted the same way as a hovered one
+    match interaction {
+        Interaction::Pressed => theme.button_pressed,
+        Interaction::Hovered => theme.button_hover,
+        Interaction::None if focused => theme.button_hover,
+        Interaction::None => theme.button,
+    }
 }
 
 pub fn interact_button(
-    mut q_button: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>,
-    primary_color_hue: Res<PrimaryColorHue>,
+    mut q_button: Query<
+        (Entity, &Interaction, &mut BackgroundColor),
+        (With<Button>, With<Focusable>),
+    >,
+    q_inactive: Query<(), InactiveButton>,
+    focused: Res<Focused>,
+    theme: Res<UiTheme>,
 ) {
-    if let Some((interaction, mut background_color)) = q_button.iter_mut().next() {
-        match interaction {
-            Interaction::Hovered => {
-                background_color.0 = button_hover_color(primary_color_hue.0);
-            }
-            Interaction::None => {
-                background_color.0 = button_background_color(primary_color_hue.0);
-            }
-            _ => {}
+    for (entity, interaction, mut background_color) in q_button.iter_mut() {
+        let color = button_color(
+            &theme,
+            *interaction,
+            focused.0 == Some(entity),
+            q_inactive.contains(entity),
+        );
+        if background_color.0 != color {
+            background_color.0 = color;
         }
     }
 }
 
-pub trait ButtonCommands<'w, 's> {
-    fn spawn_text_button<'a>(&'a mut self, text: &str, hue: f32) -> EntityCommands<'w, 's, 'a>;
+// on the raw delta, so cooldowns run out on the pause menu too
+pub fn tick_button_cooldowns(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut q_cooldown: Query<(Entity, &mut ButtonCooldown)>,
+) {
+    for (entity, mut cooldown) in q_cooldown.iter_mut() {
+        if cooldown.0.tick(time.raw_delta()).finished() {
+            commands.entity(entity).remove::<ButtonCooldown>();
+        }
+    }
 }
 
-impl<'w, 's> ButtonCommands<'w, 's> for Commands<'w, 's> {
-    fn spawn_text_button<'a>(&'a mut self, text: &str, hue: f32) -> EntityCommands<'w, 's, 'a> {
-        let mut e = self.spawn_empty();
-
-        e.insert(ButtonBundle {
-            style: Style {
-                width: Val::Px(200.0),
-                height: Val::Px(100.0),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                margin: UiRect::all(Val::Auto),
-                border: UiRect {
-                    left: Val::Px(1.0),
-                    right: Val::Px(1.0),
-                    top: Val::Px(1.0),
-                    bottom: Val::Px(4.0),
-                },
-                ..default()
+pub fn button_bundle(theme: &UiTheme, style: Style) -> ButtonBundle {
+    ButtonBundle {
+        style: Style {
+            border: UiRect {
+                left: Val::Px(1.0),
+                right: Val::Px(1.0),
+                top: Val::Px(1.0),
+                bottom: Val::Px(4.0),
             },
-            border_color: Color::BLACK.with_a(0.5).into(),
-            background_color: button_background_color(hue).into(),
-            ..default()
-        })
+            ..style
+        },
+        border_color: theme.border.into(),
+        background_color: theme.button.into(),
+        ..default()
+    }
+}
+
+pub struct TextButtonBuilder<'w, 's, 'a> {
+    commands: &'a mut Commands<'w, 's>,
+    theme: UiTheme,
+    text: String,
+    size: ButtonSize,
+}
+
+impl<'w, 's, 'a> TextButtonBuilder<'w, 's, 'a> {
+    pub fn size(mut self, size: ButtonSize) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn spawn(self) -> EntityCommands<'w, 's, 'a> {
+        let TextButtonBuilder {
+            commands,
+            theme,
+            text,
+            size,
+        } = self;
+        let mut e = commands.spawn_empty();
+
+        e.insert((
+            button_bundle(
+                &theme,
+                Style {
+                    width: Val::Px(size.width()),
+                    height: Val::Px(size.height()),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    margin: UiRect::all(Val::Auto),
+                    ..default()
+                },
+            ),
+            Focusable::default(),
+        ))
         .with_children(|parent| {
             parent.spawn(TextBundle {
                 style: Style {
                     position_type: PositionType::Absolute,
-                    margin: UiRect::top(Val::Px(4.0)),
+                    margin: UiRect::top(Val::Px(size.font_size() / 9.0)),
                     ..default()
                 },
                 text: Text::from_section(
-                    text,
+                    &text,
                     TextStyle {
-                        font_size: 36.0,
-                        color: Color::BLACK.with_a(0.5),
+                        font_size: size.font_size(),
+                        color: theme.text_shadow,
                         ..default()
                     },
                 ),
@@ -77,10 +187,10 @@ impl<'w, 's> ButtonCommands<'w, 's> for Commands<'w, 's> {
                     ..default()
                 },
                 text: Text::from_section(
-                    text,
+                    &text,
                     TextStyle {
-                        font_size: 36.0,
-                        color: Color::WHITE,
+                        font_size: size.font_size(),
+                        color: theme.text,
                         ..default()
                     },
                 ),
@@ -89,4 +199,87 @@ impl<'w, 's> ButtonCommands<'w, 's> for Commands<'w, 's> {
         });
         e
     }
-}
\ No newline at end of file
+}
+
+pub struct IconButtonBuilder<'w, 's, 'a> {
+    commands: &'a mut Commands<'w, 's>,
+    theme: UiTheme,
+    icon: Handle<Image>,
+    icon_size: f32,
+}
+
+impl<'w, 's, 'a> IconButtonBuilder<'w, 's, 'a> {
+    pub fn icon_size(mut self, icon_size: f32) -> Self {
+        self.icon_size = icon_size;
+        self
+    }
+
+    pub fn spawn(self) -> EntityCommands<'w, 's, 'a> {
+        let IconButtonBuilder {
+            commands,
+            theme,
+            icon,
+            icon_size,
+        } = self;
+        let mut e = commands.spawn_empty();
+
+        e.insert((
+            button_bundle(
+                &theme,
+                Style {
+                    padding: UiRect::all(Val::Px(5.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+            ),
+            Focusable::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn(ImageBundle {
+                style: Style {
+                    width: Val::Px(icon_size),
+                    height: Val::Px(icon_size),
+                    ..default()
+                },
+                image: icon.into(),
+                background_color: theme.text.into(),
+                ..default()
+            });
+        });
+        e
+    }
+}
+
+pub trait ButtonCommands<'w, 's> {
+    fn text_button<'a>(&'a mut self, theme: &UiTheme, text: &str) -> TextButtonBuilder<'w, 's, 'a>;
+    fn icon_button<'a>(
+        &'a mut self,
+        theme: &UiTheme,
+        icon: Handle<Image>,
+    ) -> IconButtonBuilder<'w, 's, 'a>;
+}
+
+impl<'w, 's> ButtonCommands<'w, 's> for Commands<'w, 's> {
+    fn text_button<'a>(&'a mut self, theme: &UiTheme, text: &str) -> TextButtonBuilder<'w, 's, 'a> {
+        TextButtonBuilder {
+            commands: self,
+            theme: *theme,
+            text: text.to_owned(),
+            size: ButtonSize::default(),
+        }
+    }
+
+    fn icon_button<'a>(
+        &'a mut self,
+        theme: &UiTheme,
+        icon: Handle<Image>,
+    ) -> IconButtonBuilder<'w, 's, 'a> {
+        IconButtonBuilder {
+            commands: self,
+            theme: *theme,
+            icon,
+            icon_size: 24.0,
+        }
+    }
+}
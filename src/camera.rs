@@ -0,0 +1,529 @@
+use bevy::{
+    prelude::*,
+    render::camera::ScalingMode,
+    transform::TransformSystem,
+    window::{PrimaryWindow, WindowResized},
+};
+
+use crate::camera_effects::CameraEffects;
+
+/// Keeps the orthographic projection of the `CameraRig` camera in sync with the window,
+/// and publishes the visible part of the world as `VisibleWorldRect`.
+pub struct CameraRigPlugin;
+
+impl Plugin for CameraRigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<VisibleWorldRect>()
+            .init_resource::<SafeArea>()
+            .init_resource::<CursorPlane>()
+            .init_resource::<CursorWorldPosition>()
+            .add_systems(PreUpdate, update_cursor_world_position)
+            .add_systems(
+                PostUpdate,
+                (
+                    update_camera_rig.before(TransformSystem::TransformPropagate),
+                    apply_safe_area_padding,
+                ),
+            );
+
+        #[cfg(target_arch = "wasm32")]
+        app.add_systems(PreUpdate, read_web_safe_area);
+    }
+}
+
+/// How much of the world an orthographic camera shows, independent of the window size.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CameraScaling {
+    /// Always this many world units tall; the width follows the aspect ratio.
+    FixedVertical(f32),
+    /// Always this many world units wide; the height follows the aspect ratio.
+    FixedHorizontal(f32),
+    /// At least this area is visible, extended along one axis to fill the window.
+    FitMinArea { width: f32, height: f32 },
+}
+
+impl CameraScaling {
+    pub fn scaling_mode(&self) -> ScalingMode {
+        match *self {
+            CameraScaling::FixedVertical(height) => ScalingMode::FixedVertical(height),
+            CameraScaling::FixedHorizontal(width) => ScalingMode::FixedHorizontal(width),
+            CameraScaling::FitMinArea { width, height } => ScalingMode::AutoMin {
+                min_width: width,
+                min_height: height,
+            },
+        }
+    }
+
+    /// Size of the visible world for a window with the given aspect ratio (width / height).
+    pub fn visible_size(&self, aspect_ratio: f32) -> Vec2 {
+        let aspect_ratio = aspect_ratio.max(f32::EPSILON);
+        match *self {
+            CameraScaling::FixedVertical(height) => Vec2::new(height * aspect_ratio, height),
+            CameraScaling::FixedHorizontal(width) => Vec2::new(width, width / aspect_ratio),
+            CameraScaling::FitMinArea { width, height } => {
+                if aspect_ratio > width / height {
+                    Vec2::new(height * aspect_ratio, height)
+                } else {
+                    Vec2::new(width, width / aspect_ratio)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+pub struct CameraRig {
+    pub scaling: CameraScaling,
+}
+
+/// The part of the XY plane the rig camera shows, in world units.
+#[derive(Resource, Clone, Copy, Default, PartialEq, Debug)]
+pub struct VisibleWorldRect {
+    pub rect: Rect,
+    /// `rect` without the `SafeArea` margins.
+    pub safe: Rect,
+}
+
+/// Screen margins to keep gameplay and UI out of, e.g. under a mobile notch,
+/// in logical pixels. Read from the browser on the web; zero on native platforms.
+#[derive(Resource, Clone, Copy, Default, PartialEq, Debug)]
+pub struct SafeArea {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+/// Pads a UI node by the `SafeArea`; use it on full screen roots.
+#[derive(Component)]
+pub struct SafeAreaPadding;
+
+/// The plane `CursorWorldPosition` is projected onto.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct CursorPlane {
+    pub origin: Vec3,
+    pub normal: Vec3,
+}
+
+impl Default for CursorPlane {
+    fn default() -> Self {
+        // where the panda stands
+        Self {
+            origin: Vec3::new(0.0, 0.0, -3.0),
+            normal: Vec3::Z,
+        }
+    }
+}
+
+/// The mouse cursor, or else the first touch, on the `CursorPlane`.
+/// `None` when neither is over the window.
+#[derive(Resource, Clone, Copy, Default, PartialEq, Debug)]
+pub struct CursorWorldPosition(pub Option<Vec3>);
+
+/// Where a ray hits a plane, if it hits it in front of its origin.
+pub fn ray_plane_intersection(
+    ray_origin: Vec3,
+    ray_direction: Vec3,
+    plane_origin: Vec3,
+    plane_normal: Vec3,
+) -> Option<Vec3> {
+    let denominator = plane_normal.dot(ray_direction);
+    if denominator.abs() <= f32::EPSILON {
+        return None;
+    }
+    let distance = (plane_origin - ray_origin).dot(plane_normal) / denominator;
+    (distance >= 0.0).then(|| ray_origin + ray_direction * distance)
+}
+
+/// Projects a window position through the camera onto a plane.
+///
+/// `viewport_position` is in logical pixels from the top left, like
+/// `Window::cursor_position`, so the window scale factor and the canvas size on web
+/// are already accounted for.
+pub fn viewport_to_plane(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    viewport_position: Vec2,
+    plane: &CursorPlane,
+) -> Option<Vec3> {
+    let ray = camera.viewport_to_world(camera_transform, viewport_position)?;
+    ray_plane_intersection(ray.origin, ray.direction, plane.origin, plane.normal)
+}
+
+/// The visible rect around `center`, and the same rect inset by the safe area.
+pub fn visible_world_rect(
+    scaling: &CameraScaling,
+    center: Vec2,
+    window_size: Vec2,
+    safe_area: &SafeArea,
+) -> VisibleWorldRect {
+    let size = scaling.visible_size(window_size.x / window_size.y.max(1.0));
+    let rect = Rect::from_center_size(center, size);
+
+    let world_per_pixel = size / window_size.max(Vec2::ONE);
+    let safe = Rect {
+        min: rect.min + Vec2::new(safe_area.left, safe_area.bottom) * world_per_pixel,
+        max: rect.max - Vec2::new(safe_area.right, safe_area.top) * world_per_pixel,
+    };
+    // margins larger than the window leave nothing
+    let safe = Rect {
+        max: safe.max.max(safe.min),
+        ..safe
+    };
+
+    VisibleWorldRect { rect, safe }
+}
+
+fn update_camera_rig(
+    mut resize_reader: EventReader<WindowResized>,
+    safe_area: Res<SafeArea>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_camera: Query<(
+        Ref<CameraRig>,
+        &GlobalTransform,
+        Option<&CameraEffects>,
+        &mut Projection,
+    )>,
+    mut world_rect: ResMut<VisibleWorldRect>,
+) {
+    let resized = resize_reader.iter().last().is_some();
+    let Ok(window) = q_window.get_single() else {
+        return;
+    };
+
+    for (rig, global_transform, effects, mut projection) in q_camera.iter_mut() {
+        if resized || rig.is_changed() {
+            if let Projection::Orthographic(orthographic) = projection.as_mut() {
+                orthographic.scaling_mode = rig.scaling.scaling_mode();
+            }
+        }
+
+        // shake and zoom punches don't count
+        let center = effects
+            .and_then(|effects| effects.anchor)
+            .unwrap_or(global_transform.translation().truncate());
+        world_rect.set_if_neq(visible_world_rect(
+            &rig.scaling,
+            center,
+            Vec2::new(window.width(), window.height()),
+            &safe_area,
+        ));
+    }
+}
+
+/// Once at startup, then again whenever the window resizes, e.g. when a phone rotates.
+#[cfg(target_arch = "wasm32")]
+fn read_web_safe_area(
+    mut resize_reader: EventReader<WindowResized>,
+    mut safe_area: ResMut<SafeArea>,
+    mut read: Local<bool>,
+) {
+    let resized = resize_reader.iter().last().is_some();
+    if *read && !resized {
+        return;
+    }
+    *read = true;
+    if let Some(insets) = crate::web_event::safe_area_insets() {
+        safe_area.set_if_neq(insets);
+    }
+}
+
+fn apply_safe_area_padding(
+    safe_area: Res<SafeArea>,
+    mut q_node: Query<(Ref<SafeAreaPadding>, &mut Style)>,
+) {
+    for (padding, mut style) in q_node.iter_mut() {
+        if safe_area.is_changed() || padding.is_added() {
+            style.padding = UiRect {
+                left: Val::Px(safe_area.left),
+                right: Val::Px(safe_area.right),
+                top: Val::Px(safe_area.top),
+                bottom: Val::Px(safe_area.bottom),
+            };
+        }
+    }
+}
+
+fn update_cursor_world_position(
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    touches: Res<Touches>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<CameraRig>>,
+    plane: Res<CursorPlane>,
+    mut cursor_world_position: ResMut<CursorWorldPosition>,
+) {
+    let viewport_position = q_window
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .or_else(|| touches.first_pressed_position());
+
+    let position = viewport_position.and_then(|viewport_position| {
+        let (camera, camera_transform) = q_camera.get_single().ok()?;
+        viewport_to_plane(camera, camera_transform, viewport_position, &plane)
+    });
+    cursor_world_position.set_if_neq(CursorWorldPosition(position));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{
+        render::camera::{camera_system, ManualTextureViews},
+        window::{WindowCreated, WindowResolution},
+    };
+
+    static SIXTEEN_BY_NINE: CameraScaling = CameraScaling::FitMinArea {
+        width: 16.0,
+        height: 9.0,
+    };
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(
+            actual.abs_diff_eq(expected, 1e-3),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    /// Where the center of an orthographic camera with this transform points at
+    /// on the `CursorPlane`.
+    fn center_on_plane(transform: Transform) -> Option<Vec3> {
+        let plane = CursorPlane::default();
+        ray_plane_intersection(
+            transform.translation,
+            transform.forward(),
+            plane.origin,
+            plane.normal,
+        )
+    }
+
+    #[test]
+    fn ray_down_negative_z_hits_below_origin() {
+        let transform =
+            Transform::from_xyz(1.5, -2.0, 1.0).looking_at(Vec3::new(1.5, -2.0, 0.0), Vec3::Y);
+        let hit = center_on_plane(transform).unwrap();
+        assert!(
+            hit.abs_diff_eq(Vec3::new(1.5, -2.0, -3.0), 1e-5),
+            "{:?}",
+            hit
+        );
+    }
+
+    #[test]
+    fn angled_ray_hits_where_it_looks() {
+        let target = Vec3::new(2.0, 1.0, -3.0);
+        let transform = Transform::from_xyz(-3.0, -4.0, 5.0).looking_at(target, Vec3::Y);
+        let hit = center_on_plane(transform).unwrap();
+        assert!(hit.abs_diff_eq(target, 1e-4), "{:?}", hit);
+    }
+
+    #[test]
+    fn ray_parallel_to_plane_misses() {
+        let transform = Transform::from_xyz(0.0, 0.0, 1.0).looking_to(Vec3::X, Vec3::Y);
+        assert_eq!(center_on_plane(transform), None);
+    }
+
+    #[test]
+    fn plane_behind_ray_misses() {
+        let transform = Transform::from_xyz(0.0, 0.0, 1.0).looking_to(Vec3::Z, Vec3::Y);
+        assert_eq!(center_on_plane(transform), None);
+    }
+
+    #[test]
+    fn no_viewport_no_position() {
+        // the viewport size is only known once the camera has been rendered to a target
+        let position = viewport_to_plane(
+            &Camera::default(),
+            &GlobalTransform::default(),
+            Vec2::new(10.0, 10.0),
+            &CursorPlane::default(),
+        );
+        assert_eq!(position, None);
+    }
+
+    /// A window 1280x720 logical pixels large at a scale factor of 2, and the rig camera
+    /// with its viewport computed for it, 10 world units tall like in the game.
+    fn camera_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .add_asset::<Image>()
+            .init_resource::<ManualTextureViews>()
+            .init_resource::<Touches>()
+            .init_resource::<CursorPlane>()
+            .init_resource::<CursorWorldPosition>()
+            .add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .add_systems(
+                Update,
+                (camera_system::<Projection>, update_cursor_world_position).chain(),
+            );
+        app.world.spawn((
+            Window {
+                resolution: WindowResolution::new(1280.0, 720.0).with_scale_factor_override(2.0),
+                ..default()
+            },
+            PrimaryWindow,
+        ));
+
+        let rig = CameraRig {
+            scaling: CameraScaling::FixedVertical(10.0),
+        };
+        let transform = Transform::from_xyz(0.0, 0.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y);
+        let camera = app
+            .world
+            .spawn((
+                Camera3dBundle {
+                    transform,
+                    global_transform: transform.into(),
+                    projection: Projection::Orthographic(OrthographicProjection {
+                        scaling_mode: rig.scaling.scaling_mode(),
+                        ..default()
+                    }),
+                    ..default()
+                },
+                rig,
+            ))
+            .id();
+        app.update();
+        (app, camera)
+    }
+
+    #[test]
+    fn viewport_position_maps_through_the_computed_viewport() {
+        let (app, camera_entity) = camera_app();
+        let camera = app.world.get::<Camera>(camera_entity).unwrap();
+        assert_eq!(
+            camera.logical_viewport_size(),
+            Some(Vec2::new(1280.0, 720.0))
+        );
+        assert_eq!(
+            camera.physical_viewport_size(),
+            Some(UVec2::new(2560, 1440))
+        );
+        let camera_transform = app.world.get::<GlobalTransform>(camera_entity).unwrap();
+        let plane = CursorPlane::default();
+        let half_width = 5.0 * 1280.0 / 720.0;
+
+        let center = viewport_to_plane(camera, camera_transform, Vec2::new(640.0, 360.0), &plane);
+        assert!(center.unwrap().abs_diff_eq(Vec3::new(0.0, 0.0, -3.0), 1e-4));
+
+        let top_left = viewport_to_plane(camera, camera_transform, Vec2::ZERO, &plane);
+        assert!(top_left
+            .unwrap()
+            .abs_diff_eq(Vec3::new(-half_width, 5.0, -3.0), 1e-4));
+
+        // three quarters across, a quarter down
+        let position = viewport_to_plane(camera, camera_transform, Vec2::new(960.0, 180.0), &plane);
+        assert!(
+            position
+                .unwrap()
+                .abs_diff_eq(Vec3::new(half_width / 2.0, 2.5, -3.0), 1e-4),
+            "{:?}",
+            position
+        );
+    }
+
+    #[test]
+    fn cursor_is_in_logical_pixels() {
+        let (mut app, _) = camera_app();
+        let mut q_window = app.world.query::<&mut Window>();
+        q_window
+            .single_mut(&mut app.world)
+            .set_cursor_position(Some(Vec2::new(960.0, 180.0)));
+        app.update();
+
+        let position = app.world.resource::<CursorWorldPosition>().0.unwrap();
+        let half_width = 5.0 * 1280.0 / 720.0;
+        assert!(
+            position.abs_diff_eq(Vec3::new(half_width / 2.0, 2.5, -3.0), 1e-4),
+            "{:?}",
+            position
+        );
+    }
+
+    #[test]
+    fn portrait_extends_height() {
+        let rect = visible_world_rect(
+            &SIXTEEN_BY_NINE,
+            Vec2::ZERO,
+            Vec2::new(720.0, 1280.0),
+            &SafeArea::default(),
+        );
+        assert_near(rect.rect.size(), Vec2::new(16.0, 16.0 * 1280.0 / 720.0));
+        assert_near(rect.rect.center(), Vec2::ZERO);
+        assert_eq!(rect.safe, rect.rect);
+    }
+
+    #[test]
+    fn landscape_shows_min_area() {
+        let rect = visible_world_rect(
+            &SIXTEEN_BY_NINE,
+            Vec2::new(1.0, 2.0),
+            Vec2::new(1280.0, 720.0),
+            &SafeArea::default(),
+        );
+        assert_near(rect.rect.size(), Vec2::new(16.0, 9.0));
+        assert_near(rect.rect.center(), Vec2::new(1.0, 2.0));
+    }
+
+    #[test]
+    fn ultrawide_extends_width() {
+        let rect = visible_world_rect(
+            &SIXTEEN_BY_NINE,
+            Vec2::ZERO,
+            Vec2::new(3440.0, 1440.0),
+            &SafeArea::default(),
+        );
+        assert_near(rect.rect.size(), Vec2::new(9.0 * 3440.0 / 1440.0, 9.0));
+
+        // a fixed height ignores the aspect ratio
+        let rect = visible_world_rect(
+            &CameraScaling::FixedVertical(10.0),
+            Vec2::ZERO,
+            Vec2::new(3440.0, 1440.0),
+            &SafeArea::default(),
+        );
+        assert_near(rect.rect.size(), Vec2::new(10.0 * 3440.0 / 1440.0, 10.0));
+    }
+
+    #[test]
+    fn safe_area_insets_by_pixels() {
+        let safe_area = SafeArea {
+            top: 128.0,
+            bottom: 64.0,
+            ..default()
+        };
+        let rect = visible_world_rect(
+            &SIXTEEN_BY_NINE,
+            Vec2::ZERO,
+            Vec2::new(720.0, 1280.0),
+            &safe_area,
+        );
+        let world_per_pixel = rect.rect.height() / 1280.0;
+        assert_near(
+            rect.safe.min,
+            rect.rect.min + Vec2::Y * 64.0 * world_per_pixel,
+        );
+        assert_near(
+            rect.safe.max,
+            rect.rect.max - Vec2::Y * 128.0 * world_per_pixel,
+        );
+    }
+
+    #[test]
+    fn oversized_safe_area_leaves_nothing() {
+        let safe_area = SafeArea {
+            left: 1000.0,
+            right: 1000.0,
+            ..default()
+        };
+        let rect = visible_world_rect(
+            &SIXTEEN_BY_NINE,
+            Vec2::ZERO,
+            Vec2::new(1280.0, 720.0),
+            &safe_area,
+        );
+        assert_eq!(rect.safe.width(), 0.0);
+    }
+}
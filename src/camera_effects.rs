@@ -0,0 +1,485 @@
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::timestep::interpolate_transforms;
+
+/// Screen shake, zoom punches and target following for a camera with `CameraEffects`.
+///
+/// Effects are layered on top of an anchor position, so they compose: following moves the
+/// anchor, shake offsets and rolls around it, and punches scale the orthographic projection.
+/// Shake is driven by seeded noise, so the same seed and inputs give the same offsets.
+pub struct CameraEffectsPlugin;
+
+impl Plugin for CameraEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CameraShake>()
+            .add_event::<ZoomPunch>()
+            .add_systems(
+                PostUpdate,
+                (receive_camera_effects, follow_target, apply_camera_effects)
+                    .chain()
+                    // follow the rendered, interpolated position of the target
+                    .after(interpolate_transforms)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// Adds trauma to every camera with `CameraEffects`; shake grows with trauma squared.
+#[derive(Event)]
+pub struct CameraShake {
+    pub trauma: f32,
+}
+
+/// Eases in by `amount` (0.1 is 10%) and back out.
+#[derive(Event)]
+pub struct ZoomPunch {
+    pub amount: f32,
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CameraEffects {
+    pub seed: u32,
+    /// 0 to 1.
+    pub trauma: f32,
+    /// Trauma lost per second.
+    pub trauma_decay: f32,
+    /// World units at full trauma.
+    pub max_offset: f32,
+    /// Radians at full trauma.
+    pub max_roll: f32,
+    /// How fast the shake changes direction, in noise samples per second.
+    pub shake_frequency: f32,
+    /// Peak of the current zoom punch.
+    pub zoom_punch: f32,
+    /// Seconds to zoom in.
+    pub zoom_attack: f32,
+    /// Seconds to ease back out after the peak.
+    pub zoom_release: f32,
+    /// Where the camera is without shake, on the XY plane. Set from the transform when added.
+    pub anchor: Option<Vec2>,
+    /// The rotation without roll. Set from the transform when added.
+    pub base_rotation: Option<Quat>,
+    time: f32,
+    zoom_elapsed: f32,
+    /// Where the zoom punch started from, so punches in a row don't jump.
+    zoom_from: f32,
+}
+
+impl Default for CameraEffects {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            trauma: 0.0,
+            trauma_decay: 1.5,
+            max_offset: 0.3,
+            max_roll: 0.05,
+            shake_frequency: 25.0,
+            zoom_punch: 0.0,
+            zoom_attack: 0.08,
+            zoom_release: 0.3,
+            anchor: None,
+            base_rotation: None,
+            time: 0.0,
+            zoom_elapsed: 0.0,
+            zoom_from: 0.0,
+        }
+    }
+}
+
+impl CameraEffects {
+    pub fn with_seed(seed: u32) -> Self {
+        Self { seed, ..default() }
+    }
+
+    /// Advances shake time and lets trauma and zoom punch wear off.
+    pub fn tick(&mut self, delta: f32) {
+        self.time += delta;
+        self.trauma = (self.trauma - self.trauma_decay * delta).max(0.0);
+        self.zoom_elapsed += delta;
+        if self.zoom_elapsed >= self.zoom_attack + self.zoom_release {
+            self.zoom_punch = 0.0;
+            self.zoom_from = 0.0;
+        }
+    }
+
+    /// Starts a zoom punch `amount` deeper than the current zoom.
+    pub fn punch(&mut self, amount: f32) {
+        let zoom = self.zoom();
+        self.zoom_from = zoom;
+        self.zoom_punch = zoom + amount;
+        self.zoom_elapsed = 0.0;
+    }
+
+    /// How far the camera is zoomed in by the punch, fast in and smoothly back out.
+    pub fn zoom(&self) -> f32 {
+        if self.zoom_punch == 0.0 {
+            return 0.0;
+        }
+        if self.zoom_elapsed < self.zoom_attack {
+            let t = self.zoom_elapsed / self.zoom_attack;
+            let eased = 1.0 - (1.0 - t) * (1.0 - t);
+            return self.zoom_from + (self.zoom_punch - self.zoom_from) * eased;
+        }
+        let t =
+            ((self.zoom_elapsed - self.zoom_attack) / self.zoom_release.max(f32::EPSILON)).min(1.0);
+        self.zoom_punch * (1.0 - t * t * (3.0 - 2.0 * t))
+    }
+
+    /// Offset from the anchor and roll, for the current trauma and time.
+    pub fn shake(&self) -> (Vec2, f32) {
+        let shake = self.trauma * self.trauma;
+        let t = self.time * self.shake_frequency;
+        let offset = Vec2::new(
+            value_noise(self.seed, t),
+            value_noise(self.seed.wrapping_add(1), t),
+        ) * self.max_offset
+            * shake;
+        let roll = value_noise(self.seed.wrapping_add(2), t) * self.max_roll * shake;
+        (offset, roll)
+    }
+
+    /// Orthographic scale with the zoom punch applied.
+    pub fn projection_scale(&self) -> f32 {
+        1.0 / (1.0 + self.zoom())
+    }
+}
+
+/// Moves the camera anchor towards `target`, only once it leaves the dead zone.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct CameraFollow {
+    /// Should be a top-level entity, its `Transform` is read as its world position.
+    pub target: Entity,
+    /// Half size of the area around the anchor the target can move in freely.
+    pub dead_zone: Vec2,
+    /// How fast the anchor catches up, higher is snappier.
+    pub smoothing: f32,
+    /// Where the anchor may go.
+    pub bounds: Option<Rect>,
+}
+
+impl CameraFollow {
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            dead_zone: Vec2::new(1.0, 0.5),
+            smoothing: 5.0,
+            bounds: None,
+        }
+    }
+}
+
+/// Smooth noise in -1..=1, the same for the same `seed` and `t`.
+pub fn value_noise(seed: u32, t: f32) -> f32 {
+    let i = t.floor();
+    let f = t - i;
+    let a = hash_to_unit(seed, i as i32);
+    let b = hash_to_unit(seed, i as i32 + 1);
+    let f = f * f * (3.0 - 2.0 * f);
+    a + (b - a) * f
+}
+
+fn hash_to_unit(seed: u32, i: i32) -> f32 {
+    let mut x = (i as u32)
+        .wrapping_mul(0x9e37_79b9)
+        .wrapping_add(seed.wrapping_mul(0x85eb_ca6b));
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+/// Where the anchor moves this frame to keep `target` within the dead zone.
+pub fn follow_step(anchor: Vec2, target: Vec2, follow: &CameraFollow, delta: f32) -> Vec2 {
+    let difference = target - anchor;
+    let outside = difference - difference.clamp(-follow.dead_zone, follow.dead_zone);
+    let catch_up = 1.0 - (-follow.smoothing * delta).exp();
+    let anchor = anchor + outside * catch_up;
+
+    match follow.bounds {
+        Some(bounds) => anchor.clamp(bounds.min, bounds.max),
+        None => anchor,
+    }
+}
+
+fn receive_camera_effects(
+    mut shakes: EventReader<CameraShake>,
+    mut punches: EventReader<ZoomPunch>,
+    mut q_camera: Query<&mut CameraEffects>,
+) {
+    let trauma: f32 = shakes.iter().map(|shake| shake.trauma).sum();
+    let punch: f32 = punches.iter().map(|punch| punch.amount).sum();
+    if trauma == 0.0 && punch == 0.0 {
+        return;
+    }
+
+    for mut effects in q_camera.iter_mut() {
+        effects.trauma = (effects.trauma + trauma).clamp(0.0, 1.0);
+        if punch != 0.0 {
+            effects.punch(punch);
+        }
+    }
+}
+
+fn follow_target(
+    time: Res<Time>,
+    mut q_camera: Query<(&mut CameraEffects, &CameraFollow, &Transform)>,
+    q_target: Query<&Transform, Without<CameraEffects>>,
+) {
+    for (mut effects, follow, transform) in q_camera.iter_mut() {
+        let Ok(target) = q_target.get(follow.target) else {
+            continue;
+        };
+        let anchor = effects.anchor.unwrap_or(transform.translation.truncate());
+        effects.anchor = Some(follow_step(
+            anchor,
+            target.translation.truncate(),
+            follow,
+            time.delta_seconds(),
+        ));
+    }
+}
+
+fn apply_camera_effects(
+    time: Res<Time>,
+    mut q_camera: Query<(&mut CameraEffects, &mut Transform, &mut Projection)>,
+) {
+    for (mut effects, mut transform, mut projection) in q_camera.iter_mut() {
+        let anchor = *effects
+            .anchor
+            .get_or_insert(transform.translation.truncate());
+        let base_rotation = *effects.base_rotation.get_or_insert(transform.rotation);
+        effects.tick(time.delta_seconds());
+
+        let (offset, roll) = effects.shake();
+        transform.translation = (anchor + offset).extend(transform.translation.z);
+        transform.rotation = base_rotation * Quat::from_rotation_z(roll);
+
+        if let Projection::Orthographic(orthographic) = projection.as_mut() {
+            orthographic.scale = effects.projection_scale();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn value_noise_is_deterministic_and_bounded() {
+        for i in 0..100 {
+            let t = i as f32 * 0.37;
+            let value = value_noise(42, t);
+            assert_eq!(value, value_noise(42, t));
+            assert!((-1.0..=1.0).contains(&value));
+        }
+        assert_ne!(value_noise(1, 0.5), value_noise(2, 0.5));
+    }
+
+    #[test]
+    fn value_noise_is_continuous_across_lattice_points() {
+        let before = value_noise(7, 3.0 - 1e-4);
+        let at = value_noise(7, 3.0);
+        assert!((before - at).abs() < 1e-3);
+    }
+
+    #[test]
+    fn shake_follows_the_seeded_noise() {
+        let mut effects = CameraEffects {
+            trauma: 1.0,
+            trauma_decay: 0.0,
+            ..CameraEffects::with_seed(7)
+        };
+        effects.tick(0.1);
+
+        let t = 0.1 * effects.shake_frequency;
+        let (offset, roll) = effects.shake();
+        assert_eq!(
+            offset,
+            Vec2::new(value_noise(7, t), value_noise(8, t)) * effects.max_offset
+        );
+        assert_eq!(roll, value_noise(9, t) * effects.max_roll);
+        assert_ne!(offset, Vec2::ZERO);
+
+        // half the trauma is a quarter of the shake
+        effects.trauma = 0.5;
+        let (half_offset, _) = effects.shake();
+        assert!(half_offset.abs_diff_eq(offset * 0.25, 1e-6));
+
+        effects.trauma = 0.0;
+        assert_eq!(effects.shake(), (Vec2::ZERO, 0.0));
+    }
+
+    #[test]
+    fn trauma_and_zoom_punch_wear_off() {
+        let mut effects = CameraEffects {
+            trauma: 1.0,
+            zoom_punch: 0.1,
+            ..default()
+        };
+        effects.tick(10.0);
+        assert_eq!(effects.trauma, 0.0);
+        assert_eq!(effects.projection_scale(), 1.0);
+    }
+
+    #[test]
+    fn zoom_punch_eases_in_and_out() {
+        let mut effects = CameraEffects::default();
+        effects.punch(0.1);
+        // no jump
+        assert_eq!(effects.zoom(), 0.0);
+
+        effects.tick(effects.zoom_attack / 2.0);
+        let rising = effects.zoom();
+        assert!(rising > 0.05 && rising < 0.1, "{}", rising);
+        effects.tick(effects.zoom_attack / 2.0);
+        assert!((effects.zoom() - 0.1).abs() < 1e-5);
+
+        // slow out of the peak, fast in the middle, slow into the rest
+        let release = effects.zoom_release;
+        let mut previous = effects.zoom();
+        let mut steps = vec![];
+        for _ in 0..10 {
+            effects.tick(release / 10.0);
+            steps.push(previous - effects.zoom());
+            previous = effects.zoom();
+        }
+        assert!(steps.iter().all(|step| *step >= 0.0));
+        assert!(steps[0] < steps[5] && steps[9] < steps[5]);
+        assert!(previous.abs() < 1e-5);
+
+        effects.tick(0.01);
+        assert_eq!(effects.zoom(), 0.0);
+        assert_eq!(effects.projection_scale(), 1.0);
+    }
+
+    #[test]
+    fn punches_in_a_row_start_from_the_current_zoom() {
+        let mut effects = CameraEffects::default();
+        effects.punch(0.1);
+        effects.tick(effects.zoom_attack + effects.zoom_release / 2.0);
+        let zoom = effects.zoom();
+
+        effects.punch(0.1);
+        assert_eq!(effects.zoom(), zoom);
+        effects.tick(effects.zoom_attack);
+        assert!((effects.zoom() - (zoom + 0.1)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn follow_waits_for_the_target_to_leave_the_dead_zone() {
+        let follow = CameraFollow::new(Entity::PLACEHOLDER);
+        let inside = Vec2::new(0.9, -0.4);
+        assert_eq!(follow_step(Vec2::ZERO, inside, &follow, 0.1), Vec2::ZERO);
+
+        // only the part outside the dead zone is caught up with, a bit each frame
+        let target = Vec2::new(3.0, 0.0);
+        let anchor = follow_step(Vec2::ZERO, target, &follow, 0.1);
+        assert!(anchor.x > 0.0 && anchor.x < 2.0, "{:?}", anchor);
+        assert_eq!(anchor.y, 0.0);
+
+        let mut anchor = Vec2::ZERO;
+        for _ in 0..100 {
+            anchor = follow_step(anchor, target, &follow, 0.1);
+        }
+        assert!(
+            anchor.abs_diff_eq(Vec2::new(2.0, 0.0), 1e-4),
+            "{:?}",
+            anchor
+        );
+    }
+
+    #[test]
+    fn follow_stays_inside_the_bounds() {
+        let follow = CameraFollow {
+            bounds: Some(Rect::new(-1.0, -0.5, 1.0, 0.5)),
+            ..CameraFollow::new(Entity::PLACEHOLDER)
+        };
+        let mut anchor = Vec2::ZERO;
+        for _ in 0..100 {
+            anchor = follow_step(anchor, Vec2::new(10.0, -10.0), &follow, 0.1);
+        }
+        assert_eq!(anchor, Vec2::new(1.0, -0.5));
+
+        // an anchor outside is pulled back in right away
+        let anchor = follow_step(Vec2::new(-3.0, 0.0), Vec2::new(-3.0, 0.0), &follow, 0.1);
+        assert_eq!(anchor, Vec2::new(-1.0, 0.0));
+    }
+
+    #[test]
+    fn following_moves_the_camera_before_the_shake() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_event::<CameraShake>()
+            .add_event::<ZoomPunch>()
+            .add_systems(
+                Update,
+                (receive_camera_effects, follow_target, apply_camera_effects).chain(),
+            );
+
+        let target = app.world.spawn(Transform::from_xyz(5.0, 0.0, -3.0)).id();
+        let camera = app
+            .world
+            .spawn((
+                Transform::from_xyz(0.0, 0.0, 1.0),
+                Projection::Orthographic(default()),
+                CameraEffects::default(),
+                CameraFollow::new(target),
+            ))
+            .id();
+        // long enough to catch up completely
+        let mut time = app.world.resource_mut::<Time>();
+        let startup = time.startup();
+        time.update_with_instant(startup);
+        time.update_with_instant(startup + Duration::from_secs(10));
+        app.update();
+
+        let effects = app.world.get::<CameraEffects>(camera).unwrap();
+        let anchor = effects.anchor.unwrap();
+        assert!(
+            anchor.abs_diff_eq(Vec2::new(4.0, 0.0), 1e-5),
+            "{:?}",
+            anchor
+        );
+        let transform = app.world.get::<Transform>(camera).unwrap();
+        assert_eq!(transform.translation, anchor.extend(1.0));
+    }
+
+    #[test]
+    fn roll_is_applied_on_top_of_the_camera_rotation() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_event::<CameraShake>()
+            .add_event::<ZoomPunch>()
+            .add_systems(
+                Update,
+                (receive_camera_effects, apply_camera_effects).chain(),
+            );
+
+        let transform = Transform::from_xyz(1.0, 2.0, 3.0).looking_at(Vec3::ZERO, Vec3::Y);
+        let camera = app
+            .world
+            .spawn((
+                transform,
+                Projection::Orthographic(default()),
+                CameraEffects::with_seed(3),
+            ))
+            .id();
+        app.world.send_event(CameraShake { trauma: 1.0 });
+        app.update();
+
+        let effects = *app.world.get::<CameraEffects>(camera).unwrap();
+        let (offset, roll) = effects.shake();
+        assert_ne!(roll, 0.0);
+        let shaken = app.world.get::<Transform>(camera).unwrap();
+        assert!(shaken
+            .rotation
+            .abs_diff_eq(transform.rotation * Quat::from_rotation_z(roll), 1e-6));
+        assert_eq!(
+            shaken.translation,
+            (Vec2::new(1.0, 2.0) + offset).extend(3.0)
+        );
+    }
+}
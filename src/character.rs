@@ -0,0 +1,357 @@
+use bevy::prelude::*;
+use std::f32::consts::FRAC_PI_2;
+
+use crate::{
+    animation::AnimationStateMachine,
+    camera::VisibleWorldRect,
+    focus::{FocusTrap, InputBlocked, StickNavigation},
+    pause::PauseState,
+    timestep::{FixedGameplaySet, GameplayTransform},
+    GameState,
+};
+
+static STICK_DEADZONE: f32 = 0.2;
+/// Below this speed the character counts as standing still.
+static RUNNING_SPEED_THRESHOLD: f32 = 0.5;
+/// Slower hits on the edge of the play field don't send `CharacterBumped`.
+static BUMP_SPEED_THRESHOLD: f32 = 2.0;
+
+/// Moves characters with keyboard or gamepad input while playing.
+///
+/// Runs in `FixedUpdate`, so the character needs an `InterpolatedTransformBundle`.
+pub struct CharacterPlugin;
+
+impl Plugin for CharacterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CharacterBumped>()
+            .add_systems(
+                FixedUpdate,
+                move_characters
+                    .in_set(FixedGameplaySet::Gameplay)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, claim_left_stick);
+    }
+}
+
+/// Movement on the play field, the XY plane seen by the orthographic camera.
+#[derive(Component, Clone, Copy)]
+pub struct CharacterController {
+    /// World units per second.
+    pub max_speed: f32,
+    /// How fast the velocity approaches the input direction, in units per second squared.
+    pub acceleration: f32,
+    /// How fast the velocity drops to zero without input, in units per second squared.
+    pub friction: f32,
+    /// Radians per second.
+    pub turn_speed: f32,
+    /// Kept this far from the edges of the safe part of the visible area.
+    pub bounds_margin: f32,
+    pub velocity: Vec2,
+    /// -1.0 when facing left, 1.0 when facing right.
+    pub facing: f32,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            max_speed: 4.0,
+            acceleration: 30.0,
+            friction: 20.0,
+            turn_speed: 12.0,
+            bounds_margin: 0.5,
+            velocity: Vec2::ZERO,
+            facing: 1.0,
+        }
+    }
+}
+
+impl CharacterController {
+    pub fn is_moving(&self) -> bool {
+        self.velocity.length() > RUNNING_SPEED_THRESHOLD
+    }
+}
+
+/// Sent when a character runs into the edge of the play field.
+#[derive(Event)]
+pub struct CharacterBumped {
+    pub position: Vec3,
+    /// Speed towards the edge, in world units per second.
+    pub speed: f32,
+}
+
+/// Accelerates towards `input * max_speed`, or slows down by friction without input.
+pub fn step_velocity(controller: &CharacterController, input: Vec2, delta: f32) -> Vec2 {
+    let input = input.clamp_length_max(1.0);
+    if input == Vec2::ZERO {
+        return move_towards(controller.velocity, Vec2::ZERO, controller.friction * delta);
+    }
+    move_towards(
+        controller.velocity,
+        input * controller.max_speed,
+        controller.acceleration * delta,
+    )
+}
+
+fn move_towards(current: Vec2, target: Vec2, max_delta: f32) -> Vec2 {
+    let difference = target - current;
+    if difference.length() <= max_delta {
+        target
+    } else {
+        current + difference.normalize() * max_delta
+    }
+}
+
+fn movement_input(
+    keyboard_input: &Input<KeyCode>,
+    gamepads: &Gamepads,
+    axes: &Axis<GamepadAxis>,
+) -> Vec2 {
+    let mut input = Vec2::ZERO;
+    if keyboard_input.any_pressed([KeyCode::A, KeyCode::Left]) {
+        input.x -= 1.0;
+    }
+    if keyboard_input.any_pressed([KeyCode::D, KeyCode::Right]) {
+        input.x += 1.0;
+    }
+    if keyboard_input.any_pressed([KeyCode::S, KeyCode::Down]) {
+        input.y -= 1.0;
+    }
+    if keyboard_input.any_pressed([KeyCode::W, KeyCode::Up]) {
+        input.y += 1.0;
+    }
+
+    for gamepad in gamepads.iter() {
+        let stick = Vec2::new(
+            axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                .unwrap_or_default(),
+            axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                .unwrap_or_default(),
+        );
+        if stick.length() > STICK_DEADZONE {
+            input += stick;
+        }
+    }
+
+    input.clamp_length_max(1.0)
+}
+
+/// The left stick moves the character while playing, and only moves focus while the game
+/// is paused or something traps focus.
+fn claim_left_stick(
+    state: Res<State<GameState>>,
+    pause_state: Res<State<PauseState>>,
+    q_trap: Query<(), With<FocusTrap>>,
+    mut stick_navigation: ResMut<StickNavigation>,
+) {
+    let moving = state.get() == &GameState::Playing
+        && pause_state.get() == &PauseState::Running
+        && q_trap.is_empty();
+    stick_navigation.set_if_neq(StickNavigation(!moving));
+}
+
+fn move_characters(
+    time: Res<FixedTime>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    input_blocked: Res<InputBlocked>,
+    world_rect: Res<VisibleWorldRect>,
+    mut bumped: EventWriter<CharacterBumped>,
+    mut q_character: Query<(
+        &mut CharacterController,
+        &mut GameplayTransform,
+        Option<&mut AnimationStateMachine>,
+    )>,
+) {
+    let delta = time.period.as_secs_f32();
+    // e.g. during a screen transition, the character slows down to a stop
+    let input = if input_blocked.0 {
+        Vec2::ZERO
+    } else {
+        movement_input(&keyboard_input, &gamepads, &axes)
+    };
+    let play_field = world_rect.safe;
+
+    for (mut controller, mut gameplay_transform, state_machine) in q_character.iter_mut() {
+        controller.velocity = step_velocity(&controller, input, delta);
+
+        let transform = &mut gameplay_transform.0;
+        let min = play_field.min + controller.bounds_margin;
+        let max = (play_field.max - controller.bounds_margin).max(min);
+        let unclamped = transform.translation.truncate() + controller.velocity * delta;
+        let position = unclamped.clamp(min, max);
+        // stop pushing against the edges
+        let mut impact = Vec2::ZERO;
+        if position.x != unclamped.x {
+            impact.x = controller.velocity.x;
+            controller.velocity.x = 0.0;
+        }
+        if position.y != unclamped.y {
+            impact.y = controller.velocity.y;
+            controller.velocity.y = 0.0;
+        }
+        transform.translation = position.extend(transform.translation.z);
+        if impact.length() >= BUMP_SPEED_THRESHOLD {
+            bumped.send(CharacterBumped {
+                position: transform.translation,
+                speed: impact.length(),
+            });
+        }
+
+        if controller.velocity.x.abs() > 0.01 {
+            controller.facing = controller.velocity.x.signum();
+        }
+        // the model faces the camera, turn it sideways towards where it's heading
+        let target = Quat::from_rotation_y(controller.facing * FRAC_PI_2);
+        transform.rotation = transform
+            .rotation
+            .slerp(target, (controller.turn_speed * delta).min(1.0));
+
+        if let Some(mut state_machine) = state_machine {
+            let running = controller.is_moving();
+            if state_machine.running != running {
+                state_machine.running = running;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    static DELTA: f32 = 0.1;
+
+    #[test]
+    fn accelerates_up_to_max_speed() {
+        let mut controller = CharacterController::default();
+        controller.velocity = step_velocity(&controller, Vec2::X, DELTA);
+        assert!((controller.velocity.x - controller.acceleration * DELTA).abs() < 1e-5);
+
+        for _ in 0..10 {
+            controller.velocity = step_velocity(&controller, Vec2::X, DELTA);
+        }
+        assert_eq!(controller.velocity, Vec2::new(controller.max_speed, 0.0));
+
+        // diagonal input is no faster
+        controller.velocity = Vec2::ZERO;
+        for _ in 0..10 {
+            controller.velocity = step_velocity(&controller, Vec2::ONE, DELTA);
+        }
+        assert!((controller.velocity.length() - controller.max_speed).abs() < 1e-5);
+    }
+
+    #[test]
+    fn decelerates_towards_a_slower_input() {
+        let mut controller = CharacterController {
+            velocity: Vec2::new(4.0, 0.0),
+            ..default()
+        };
+        controller.velocity = step_velocity(&controller, Vec2::new(0.5, 0.0), DELTA);
+        assert_eq!(controller.velocity, Vec2::new(2.0, 0.0));
+    }
+
+    #[test]
+    fn stops_by_friction_without_input() {
+        let mut controller = CharacterController {
+            velocity: Vec2::new(0.0, 4.0),
+            ..default()
+        };
+        controller.velocity = step_velocity(&controller, Vec2::ZERO, DELTA);
+        assert!((controller.velocity.y - (4.0 - controller.friction * DELTA)).abs() < 1e-5);
+
+        controller.velocity = step_velocity(&controller, Vec2::ZERO, DELTA);
+        assert_eq!(controller.velocity, Vec2::ZERO);
+        assert!(!controller.is_moving());
+    }
+
+    fn movement_app(controller: CharacterController, position: Vec2) -> (App, Entity) {
+        let mut app = App::new();
+        app.insert_resource(FixedTime::new(Duration::from_secs_f32(DELTA)))
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Gamepads>()
+            .init_resource::<Axis<GamepadAxis>>()
+            .init_resource::<InputBlocked>()
+            .insert_resource(VisibleWorldRect {
+                rect: Rect::new(-6.0, -4.0, 6.0, 4.0),
+                safe: Rect::new(-5.0, -3.0, 5.0, 3.0),
+            })
+            .add_event::<CharacterBumped>()
+            .add_systems(Update, move_characters);
+        let character = app
+            .world
+            .spawn((
+                controller,
+                GameplayTransform(Transform::from_translation(position.extend(-3.0))),
+            ))
+            .id();
+        (app, character)
+    }
+
+    fn bumps(app: &mut App) -> Vec<CharacterBumped> {
+        app.world
+            .resource_mut::<Events<CharacterBumped>>()
+            .drain()
+            .collect()
+    }
+
+    fn translation(app: &App, character: Entity) -> Vec3 {
+        app.world
+            .get::<GameplayTransform>(character)
+            .unwrap()
+            .0
+            .translation
+    }
+
+    fn running(velocity: Vec2) -> CharacterController {
+        CharacterController {
+            velocity,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn stays_inside_the_safe_rect() {
+        // running right into the edge, the margin keeps it 0.5 inside
+        let (mut app, character) = movement_app(running(Vec2::new(4.0, 0.0)), Vec2::new(4.4, 2.8));
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Right);
+        app.update();
+
+        let translation = translation(&app, character);
+        assert_eq!(translation, Vec3::new(4.5, 2.5, -3.0));
+        let controller = app.world.get::<CharacterController>(character).unwrap();
+        assert_eq!(controller.velocity, Vec2::ZERO);
+
+        let bumps = bumps(&mut app);
+        assert_eq!(bumps.len(), 1);
+        assert_eq!(bumps[0].position, translation);
+        assert_eq!(bumps[0].speed, 4.0);
+    }
+
+    #[test]
+    fn slow_hits_and_free_movement_dont_bump() {
+        let slow = CharacterController {
+            max_speed: 1.5,
+            ..running(Vec2::new(1.5, 0.0))
+        };
+        let (mut app, character) = movement_app(slow, Vec2::new(4.45, 0.0));
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Right);
+        app.update();
+        assert_eq!(translation(&app, character).x, 4.5);
+        assert!(bumps(&mut app).is_empty());
+
+        let (mut app, character) = movement_app(running(Vec2::new(-4.0, 0.0)), Vec2::ZERO);
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Left);
+        app.update();
+        assert!((translation(&app, character).x + 0.4).abs() < 1e-5);
+        assert!(bumps(&mut app).is_empty());
+    }
+}
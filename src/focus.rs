@@ -0,0 +1,700 @@
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+use bevy_tweening::{EaseFunction, Tween};
+use std::time::Duration;
+
+use crate::{
+    button::InactiveButton,
+    lenses::{MaterialTweenCommands, StandardMaterialBaseColorLens},
+    theme::UiTheme,
+};
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Focused>()
+            .init_resource::<InputBlocked>()
+            .init_resource::<StickNavigation>()
+            .add_event::<FocusActivated>()
+            .add_event::<PickActivated>()
+            .add_event::<FocusAdjusted>()
+            .add_event::<FocusBack>()
+            .add_systems(
+                Update,
+                (
+                    clear_unreachable_focus,
+                    focus_hovered_button,
+                    navigate_focus,
+                    activate_pressed_button,
+                    activate_picked_button,
+                    highlight_focused_3d_button,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// While set, focus doesn't move and nothing gets activated, e.g. during a screen transition.
+#[derive(Resource, Default)]
+pub struct InputBlocked(pub bool);
+
+/// Whether the left stick moves focus. The D-pad always does.
+#[derive(Resource, Clone, Copy, PartialEq, Eq)]
+pub struct StickNavigation(pub bool);
+
+impl Default for StickNavigation {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Marks a UI or 3D button as reachable by focus navigation.
+/// Buttons are visited in ascending `order`.
+#[derive(Component, Default)]
+pub struct Focusable {
+    pub order: i32,
+}
+
+#[derive(Resource, Default)]
+pub struct Focused(pub Option<Entity>);
+
+/// Sent when a focusable button is activated by mouse, keyboard or gamepad.
+/// Never sent for disabled buttons or buttons on cooldown.
+#[derive(Event)]
+pub struct FocusActivated(pub Entity);
+
+/// Sent by the picking handler of 3D buttons, and turned into `FocusActivated`
+/// unless the button is inactive.
+#[derive(Event)]
+pub struct PickActivated(pub Entity);
+
+impl From<ListenerInput<Pointer<Click>>> for PickActivated {
+    fn from(event: ListenerInput<Pointer<Click>>) -> Self {
+        PickActivated(event.listener())
+    }
+}
+
+/// Sent when the "back" button is pressed.
+#[derive(Event)]
+pub struct FocusBack;
+
+/// Sent when left/right is pressed while an adjustable widget (like a slider) is focused.
+/// The direction is -1 or 1.
+#[derive(Event)]
+pub struct FocusAdjusted(pub Entity, pub i32);
+
+/// Left/right adjusts this focusable instead of moving focus.
+#[derive(Component)]
+pub struct FocusAdjustable;
+
+/// While a trap exists, only focusables inside the innermost one can be navigated to.
+/// Used for overlays like the settings screen.
+#[derive(Component)]
+pub struct FocusTrap;
+
+static STICK_PRESS_THRESHOLD: f32 = 0.5;
+static STICK_RELEASE_THRESHOLD: f32 = 0.3;
+
+static HIGHLIGHT_SECS: f32 = 0.15;
+
+/// The color a 3D button is fading to.
+#[derive(Component)]
+struct HighlightColor(Color);
+
+/// Moves focus `step` entries through `order`, wrapping around at the ends.
+/// With nothing focused, the first entry (or last, when stepping backwards) is picked.
+pub fn step_focus(order: &[Entity], current: Option<Entity>, step: i32) -> Option<Entity> {
+    if order.is_empty() {
+        return None;
+    }
+
+    let len = order.len() as i32;
+    let index = match current.and_then(|e| order.iter().position(|o| *o == e)) {
+        Some(index) => (index as i32 + step).rem_euclid(len),
+        None if step < 0 => len - 1,
+        None => 0,
+    };
+
+    Some(order[index as usize])
+}
+
+fn ancestors(entity: Entity, q_parent: &Query<&Parent>) -> Vec<Entity> {
+    let mut ancestors = vec![];
+    let mut current = entity;
+    while let Ok(parent) = q_parent.get(current) {
+        current = parent.get();
+        ancestors.push(current);
+    }
+    ancestors
+}
+
+fn focus_order(
+    q_focusable: &Query<(Entity, &Focusable, Option<&ComputedVisibility>)>,
+    q_trap: &Query<Entity, With<FocusTrap>>,
+    q_parent: &Query<&Parent>,
+) -> Vec<Entity> {
+    let innermost_trap = innermost_trap(q_trap, q_parent);
+
+    let mut focusables: Vec<(i32, Entity)> = q_focusable
+        .iter()
+        .filter(|(_, _, visibility)| visibility.map_or(true, |v| v.is_visible()))
+        .filter(|(entity, _, _)| {
+            innermost_trap.map_or(true, |trap| ancestors(*entity, q_parent).contains(&trap))
+        })
+        .map(|(entity, focusable, _)| (focusable.order, entity))
+        .collect();
+    focusables.sort();
+    focusables.into_iter().map(|(_, entity)| entity).collect()
+}
+
+fn innermost_trap(
+    q_trap: &Query<Entity, With<FocusTrap>>,
+    q_parent: &Query<&Parent>,
+) -> Option<Entity> {
+    q_trap
+        .iter()
+        .max_by_key(|trap| ancestors(*trap, q_parent).len())
+}
+
+/// Whether a focus trap exists that `entity` isn't inside of.
+fn trapped_outside(
+    entity: Entity,
+    q_trap: &Query<Entity, With<FocusTrap>>,
+    q_parent: &Query<&Parent>,
+) -> bool {
+    innermost_trap(q_trap, q_parent)
+        .map_or(false, |trap| !ancestors(entity, q_parent).contains(&trap))
+}
+
+/// Drops focus from despawned buttons and from buttons outside the innermost focus trap,
+/// so nothing behind an overlay can be activated.
+fn clear_unreachable_focus(
+    mut focused: ResMut<Focused>,
+    q_focusable: Query<(), With<Focusable>>,
+    q_trap: Query<Entity, With<FocusTrap>>,
+    q_parent: Query<&Parent>,
+) {
+    if let Some(entity) = focused.0 {
+        if !q_focusable.contains(entity) || trapped_outside(entity, &q_trap, &q_parent) {
+            focused.0 = None;
+        }
+    }
+}
+
+fn focus_hovered_button(
+    q_button: Query<(Entity, &Interaction), (Changed<Interaction>, With<Focusable>)>,
+    q_trap: Query<Entity, With<FocusTrap>>,
+    q_parent: Query<&Parent>,
+    mut focused: ResMut<Focused>,
+    input_blocked: Res<InputBlocked>,
+) {
+    if input_blocked.0 {
+        return;
+    }
+    for (entity, interaction) in q_button.iter() {
+        if *interaction == Interaction::Hovered
+            && focused.0 != Some(entity)
+            && !trapped_outside(entity, &q_trap, &q_parent)
+        {
+            focused.0 = Some(entity);
+        }
+    }
+}
+
+/// Reads the D-pad and, with `use_stick`, the left stick of every connected gamepad.
+/// Each axis is -1 (left/up), 1 (right/down) or 0.
+fn gamepad_navigation(
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+    use_stick: bool,
+    stick_held: &mut bool,
+) -> IVec2 {
+    let mut navigation = IVec2::ZERO;
+    let mut stick = Vec2::ZERO;
+
+    for gamepad in gamepads.iter() {
+        let pressed = |button_type| buttons.just_pressed(GamepadButton::new(gamepad, button_type));
+
+        if pressed(GamepadButtonType::DPadLeft) {
+            navigation.x -= 1;
+        }
+        if pressed(GamepadButtonType::DPadRight) {
+            navigation.x += 1;
+        }
+        if pressed(GamepadButtonType::DPadUp) {
+            navigation.y -= 1;
+        }
+        if pressed(GamepadButtonType::DPadDown) {
+            navigation.y += 1;
+        }
+
+        let axis = |axis_type| {
+            axes.get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or(0.0)
+        };
+        let gamepad_stick = Vec2::new(
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        );
+        if gamepad_stick.length() > stick.length() {
+            stick = gamepad_stick;
+        }
+    }
+
+    let stick_magnitude = stick.x.abs().max(stick.y.abs());
+    if !use_stick {
+        // a stick still held when it's handed back has to be released first
+        *stick_held = stick_magnitude >= STICK_RELEASE_THRESHOLD;
+    } else if stick_magnitude < STICK_RELEASE_THRESHOLD {
+        *stick_held = false;
+    } else if stick_magnitude > STICK_PRESS_THRESHOLD && !*stick_held {
+        *stick_held = true;
+        // stick up is positive y, but moves focus up the list
+        if stick.y.abs() > stick.x.abs() {
+            navigation.y -= stick.y.signum() as i32;
+        } else {
+            navigation.x += stick.x.signum() as i32;
+        }
+    }
+
+    navigation.signum()
+}
+
+fn keyboard_navigation(keyboard_input: &Input<KeyCode>) -> IVec2 {
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let mut navigation = IVec2::ZERO;
+
+    if keyboard_input.just_pressed(KeyCode::Left) {
+        navigation.x -= 1;
+    }
+    if keyboard_input.just_pressed(KeyCode::Right) {
+        navigation.x += 1;
+    }
+    if keyboard_input.just_pressed(KeyCode::Up)
+        || (shift && keyboard_input.just_pressed(KeyCode::Tab))
+    {
+        navigation.y -= 1;
+    }
+    if keyboard_input.just_pressed(KeyCode::Down)
+        || (!shift && keyboard_input.just_pressed(KeyCode::Tab))
+    {
+        navigation.y += 1;
+    }
+
+    navigation
+}
+
+fn navigate_focus(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    stick_navigation: Res<StickNavigation>,
+    mut stick_held: Local<bool>,
+    q_focusable: Query<(Entity, &Focusable, Option<&ComputedVisibility>)>,
+    q_adjustable: Query<(), With<FocusAdjustable>>,
+    q_inactive: Query<(), InactiveButton>,
+    q_trap: Query<Entity, With<FocusTrap>>,
+    q_parent: Query<&Parent>,
+    mut focused: ResMut<Focused>,
+    mut activated: EventWriter<FocusActivated>,
+    mut adjusted: EventWriter<FocusAdjusted>,
+    mut back: EventWriter<FocusBack>,
+    input_blocked: Res<InputBlocked>,
+) {
+    let navigation = (gamepad_navigation(
+        &gamepads,
+        &buttons,
+        &axes,
+        stick_navigation.0,
+        &mut stick_held,
+    ) + keyboard_navigation(&keyboard_input))
+    .signum();
+    if input_blocked.0 {
+        return;
+    }
+
+    let mut step = navigation.y;
+    if navigation.x != 0 {
+        match focused.0 {
+            Some(entity) if q_adjustable.contains(entity) => {
+                if !q_inactive.contains(entity) {
+                    adjusted.send(FocusAdjusted(entity, navigation.x));
+                }
+            }
+            _ => step += navigation.x,
+        }
+    }
+
+    if step != 0 {
+        let order = focus_order(&q_focusable, &q_trap, &q_parent);
+        let next = step_focus(&order, focused.0, step.signum());
+        if focused.0 != next {
+            focused.0 = next;
+        }
+    }
+
+    let gamepad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+
+    if gamepad_pressed(GamepadButtonType::South) || keyboard_input.just_pressed(KeyCode::Return) {
+        if let Some(entity) = focused.0.filter(|entity| !q_inactive.contains(*entity)) {
+            activated.send(FocusActivated(entity));
+        }
+    }
+    if gamepad_pressed(GamepadButtonType::East) || keyboard_input.just_pressed(KeyCode::Back) {
+        back.send(FocusBack);
+    }
+}
+
+fn activate_pressed_button(
+    q_button: Query<(Entity, &Interaction), (Changed<Interaction>, With<Focusable>)>,
+    q_inactive: Query<(), InactiveButton>,
+    mut activated: EventWriter<FocusActivated>,
+    input_blocked: Res<InputBlocked>,
+) {
+    for (entity, interaction) in q_button.iter() {
+        if *interaction == Interaction::Pressed && !q_inactive.contains(entity) && !input_blocked.0
+        {
+            activated.send(FocusActivated(entity));
+        }
+    }
+}
+
+/// Pointer clicks reach 3D buttons behind UI overlays, so these check the focus trap too.
+fn activate_picked_button(
+    mut picked: EventReader<PickActivated>,
+    q_inactive: Query<(), InactiveButton>,
+    q_trap: Query<Entity, With<FocusTrap>>,
+    q_parent: Query<&Parent>,
+    mut activated: EventWriter<FocusActivated>,
+    input_blocked: Res<InputBlocked>,
+) {
+    for PickActivated(entity) in picked.iter() {
+        if !q_inactive.contains(*entity)
+            && !input_blocked.0
+            && !trapped_outside(*entity, &q_trap, &q_parent)
+        {
+            activated.send(FocusActivated(*entity));
+        }
+    }
+}
+
+/// Tints the focused 3D button like a hovered UI button, and dims inactive ones.
+/// The tint fades on the button's own copy of its material, which is otherwise
+/// shared with its warmup copy.
+fn highlight_focused_3d_button(
+    mut commands: Commands,
+    focused: Res<Focused>,
+    q_button: Query<(Entity, &Handle<StandardMaterial>, Option<&HighlightColor>), With<Focusable>>,
+    q_inactive: Query<(), InactiveButton>,
+    standard_materials: Res<Assets<StandardMaterial>>,
+    theme: Res<UiTheme>,
+) {
+    for (entity, material_handle, highlight) in q_button.iter() {
+        let color = if q_inactive.contains(entity) {
+            Color::GRAY
+        } else if focused.0 == Some(entity) {
+            theme.button_hover
+        } else {
+            Color::WHITE
+        };
+
+        if highlight.map(|highlight| highlight.0) == Some(color) {
+            continue;
+        }
+        let Some(start) = standard_materials
+            .get(material_handle)
+            .map(|material| material.base_color)
+        else {
+            continue;
+        };
+
+        commands.entity(entity).insert(HighlightColor(color));
+        if start != color {
+            commands.animate_material(
+                entity,
+                Tween::new(
+                    EaseFunction::QuadraticOut,
+                    Duration::from_secs_f32(HIGHLIGHT_SECS),
+                    StandardMaterialBaseColorLens { start, end: color },
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::button::{tick_button_cooldowns, ButtonCooldown};
+    use bevy::input::gamepad::{
+        gamepad_connection_system, GamepadConnection, GamepadConnectionEvent, GamepadInfo,
+    };
+    use std::time::Duration;
+
+    fn navigation_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<Gamepads>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<Axis<GamepadAxis>>()
+            .init_resource::<Axis<GamepadButton>>()
+            .init_resource::<Focused>()
+            .init_resource::<InputBlocked>()
+            .init_resource::<StickNavigation>()
+            .add_event::<GamepadConnectionEvent>()
+            .add_event::<FocusActivated>()
+            .add_event::<FocusAdjusted>()
+            .add_event::<FocusBack>()
+            .add_systems(PreUpdate, gamepad_connection_system)
+            .add_systems(Update, (clear_unreachable_focus, navigate_focus).chain());
+
+        app.world.send_event(GamepadConnectionEvent::new(
+            Gamepad::new(0),
+            GamepadConnection::Connected(GamepadInfo {
+                name: "test pad".to_owned(),
+            }),
+        ));
+        app.update();
+        app
+    }
+
+    /// Presses `button` for exactly one update.
+    fn press(app: &mut App, button: GamepadButtonType) {
+        app.world
+            .resource_mut::<Input<GamepadButton>>()
+            .press(GamepadButton::new(Gamepad::new(0), button));
+        app.update();
+        let mut input = app.world.resource_mut::<Input<GamepadButton>>();
+        input.release_all();
+        input.clear();
+    }
+
+    fn drain<E: Event>(app: &mut App) -> Vec<E> {
+        app.world.resource_mut::<Events<E>>().drain().collect()
+    }
+
+    fn focused(app: &App) -> Option<Entity> {
+        app.world.resource::<Focused>().0
+    }
+
+    #[test]
+    fn dpad_moves_focus_in_order() {
+        let mut app = navigation_app();
+        let second = app.world.spawn(Focusable { order: 1 }).id();
+        let first = app.world.spawn(Focusable { order: 0 }).id();
+
+        press(&mut app, GamepadButtonType::DPadDown);
+        assert_eq!(focused(&app), Some(first));
+        press(&mut app, GamepadButtonType::DPadDown);
+        assert_eq!(focused(&app), Some(second));
+        // wraps around
+        press(&mut app, GamepadButtonType::DPadDown);
+        assert_eq!(focused(&app), Some(first));
+        press(&mut app, GamepadButtonType::DPadUp);
+        assert_eq!(focused(&app), Some(second));
+    }
+
+    #[test]
+    fn south_activates_and_east_goes_back() {
+        let mut app = navigation_app();
+        let button = app.world.spawn(Focusable { order: 0 }).id();
+        app.world.resource_mut::<Focused>().0 = Some(button);
+
+        press(&mut app, GamepadButtonType::South);
+        let activated: Vec<Entity> = drain::<FocusActivated>(&mut app)
+            .into_iter()
+            .map(|FocusActivated(entity)| entity)
+            .collect();
+        assert_eq!(activated, vec![button]);
+        assert!(drain::<FocusBack>(&mut app).is_empty());
+
+        press(&mut app, GamepadButtonType::East);
+        assert_eq!(drain::<FocusBack>(&mut app).len(), 1);
+        assert!(drain::<FocusActivated>(&mut app).is_empty());
+    }
+
+    #[test]
+    fn focus_stays_inside_trap() {
+        let mut app = navigation_app();
+        let outside = app.world.spawn(Focusable { order: 0 }).id();
+        let mut inside = vec![];
+        app.world.spawn(FocusTrap).with_children(|parent| {
+            inside.push(parent.spawn(Focusable { order: 1 }).id());
+            inside.push(parent.spawn(Focusable { order: 2 }).id());
+        });
+        app.world.resource_mut::<Focused>().0 = Some(outside);
+
+        for _ in 0..4 {
+            press(&mut app, GamepadButtonType::DPadDown);
+            let focus = focused(&app);
+            assert!(
+                focus.map_or(false, |entity| inside.contains(&entity)),
+                "focus escaped the trap: {:?}",
+                focus
+            );
+        }
+
+        // nothing outside the trap can be activated either
+        press(&mut app, GamepadButtonType::South);
+        let activated = drain::<FocusActivated>(&mut app);
+        assert!(activated
+            .iter()
+            .all(|FocusActivated(entity)| *entity != outside));
+    }
+
+    /// Lets `secs` pass for one update, which doesn't advance paused time.
+    fn advance_time(app: &mut App, secs: f32) {
+        let mut time = app.world.resource_mut::<Time>();
+        if time.last_update().is_none() {
+            let startup = time.startup();
+            time.update_with_instant(startup);
+        }
+        let now = time.last_update().unwrap() + Duration::from_secs_f32(secs);
+        time.update_with_instant(now);
+        app.update();
+        // no time passes during the following updates
+        app.world.resource_mut::<Time>().update_with_instant(now);
+    }
+
+    fn activated(app: &mut App) -> Vec<Entity> {
+        drain::<FocusActivated>(app)
+            .into_iter()
+            .map(|FocusActivated(entity)| entity)
+            .collect()
+    }
+
+    #[test]
+    fn presses_during_cooldown_are_ignored() {
+        let mut app = navigation_app();
+        app.init_resource::<Time>().add_systems(
+            Update,
+            (
+                tick_button_cooldowns.before(navigate_focus),
+                activate_pressed_button.after(navigate_focus),
+            ),
+        );
+        let button = app
+            .world
+            .spawn((
+                Focusable::default(),
+                Interaction::None,
+                ButtonCooldown::from_secs(1.0),
+            ))
+            .id();
+        app.world.resource_mut::<Focused>().0 = Some(button);
+
+        advance_time(&mut app, 0.5);
+        press(&mut app, GamepadButtonType::South);
+        *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Pressed;
+        app.update();
+        assert!(activated(&mut app).is_empty());
+        assert!(app.world.get::<ButtonCooldown>(button).is_some());
+
+        // the cooldown runs out on the raw delta, even while paused
+        *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::None;
+        app.world.resource_mut::<Time>().pause();
+        advance_time(&mut app, 0.6);
+        assert!(app.world.get::<ButtonCooldown>(button).is_none());
+
+        press(&mut app, GamepadButtonType::South);
+        assert_eq!(activated(&mut app), vec![button]);
+        *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Pressed;
+        app.update();
+        assert_eq!(activated(&mut app), vec![button]);
+    }
+
+    #[test]
+    fn picks_behind_a_trap_are_ignored() {
+        let mut app = navigation_app();
+        app.add_event::<PickActivated>()
+            .add_systems(Update, activate_picked_button);
+        let behind = app.world.spawn(Focusable::default()).id();
+        let mut inside = Entity::PLACEHOLDER;
+        app.world.spawn(FocusTrap).with_children(|parent| {
+            inside = parent.spawn(Focusable::default()).id();
+        });
+
+        app.world.send_event(PickActivated(behind));
+        app.world.send_event(PickActivated(inside));
+        app.update();
+        assert_eq!(activated(&mut app), vec![inside]);
+
+        app.world.resource_mut::<InputBlocked>().0 = true;
+        app.world.send_event(PickActivated(inside));
+        app.update();
+        assert!(activated(&mut app).is_empty());
+    }
+
+    #[test]
+    fn hovering_focuses_only_reachable_buttons_while_input_is_allowed() {
+        let mut app = navigation_app();
+        app.add_systems(Update, focus_hovered_button.before(navigate_focus));
+        let behind = app
+            .world
+            .spawn((Focusable::default(), Interaction::None))
+            .id();
+        let mut inside = Entity::PLACEHOLDER;
+        let trap = app
+            .world
+            .spawn(FocusTrap)
+            .with_children(|parent| {
+                inside = parent.spawn((Focusable::default(), Interaction::None)).id();
+            })
+            .id();
+
+        *app.world.get_mut::<Interaction>(behind).unwrap() = Interaction::Hovered;
+        app.update();
+        assert_eq!(focused(&app), None);
+
+        app.world.entity_mut(trap).remove::<FocusTrap>();
+        app.world.resource_mut::<InputBlocked>().0 = true;
+        *app.world.get_mut::<Interaction>(inside).unwrap() = Interaction::Hovered;
+        app.update();
+        assert_eq!(focused(&app), None);
+
+        app.world.resource_mut::<InputBlocked>().0 = false;
+        *app.world.get_mut::<Interaction>(behind).unwrap() = Interaction::Hovered;
+        app.update();
+        assert_eq!(focused(&app), Some(behind));
+    }
+
+    fn set_stick_y(app: &mut App, value: f32) {
+        app.world.resource_mut::<Axis<GamepadAxis>>().set(
+            GamepadAxis::new(Gamepad::new(0), GamepadAxisType::LeftStickY),
+            value,
+        );
+    }
+
+    #[test]
+    fn stick_only_navigates_when_allowed() {
+        let mut app = navigation_app();
+        let first = app.world.spawn(Focusable { order: 0 }).id();
+        let second = app.world.spawn(Focusable { order: 1 }).id();
+        app.world.resource_mut::<StickNavigation>().0 = false;
+
+        // stick down is negative y
+        set_stick_y(&mut app, -1.0);
+        app.update();
+        assert_eq!(focused(&app), None);
+        press(&mut app, GamepadButtonType::DPadDown);
+        assert_eq!(focused(&app), Some(first));
+
+        // still held when handed back, so it doesn't jump right away
+        app.world.resource_mut::<StickNavigation>().0 = true;
+        app.update();
+        assert_eq!(focused(&app), Some(first));
+
+        set_stick_y(&mut app, 0.0);
+        app.update();
+        set_stick_y(&mut app, -1.0);
+        app.update();
+        assert_eq!(focused(&app), Some(second));
+    }
+}
@@ -0,0 +1,234 @@
+use bevy::{audio::AudioSinkPlayback, ecs::system::Command, prelude::*};
+use bevy_tweening::{AssetAnimator, Lens, Tweenable};
+
+// assets animated by these lenses need `asset_animator_system`, and components other than
+// `Transform`, `Text` and `Style` need `component_animator_system`; see `TweenHelpersPlugin`
+
+fn lerp_color(start: Color, end: Color, ratio: f32) -> Color {
+    let start: Vec4 = start.into();
+    let end: Vec4 = end.into();
+    start.lerp(end, ratio).into()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StandardMaterialBaseColorLens {
+    pub start: Color,
+    pub end: Color,
+}
+
+impl Lens<StandardMaterial> for StandardMaterialBaseColorLens {
+    fn lerp(&mut self, target: &mut StandardMaterial, ratio: f32) {
+        target.base_color = lerp_color(self.start, self.end, ratio);
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StandardMaterialEmissiveLens {
+    pub start: Color,
+    pub end: Color,
+}
+
+impl Lens<StandardMaterial> for StandardMaterialEmissiveLens {
+    fn lerp(&mut self, target: &mut StandardMaterial, ratio: f32) {
+        target.emissive = lerp_color(self.start, self.end, ratio);
+    }
+}
+
+/// Only changes the alpha of the base color. Opaque materials are switched to
+/// `AlphaMode::Blend` while they're see-through.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StandardMaterialAlphaLens {
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Lens<StandardMaterial> for StandardMaterialAlphaLens {
+    fn lerp(&mut self, target: &mut StandardMaterial, ratio: f32) {
+        let alpha = self.start + (self.end - self.start) * ratio;
+        target.base_color.set_a(alpha);
+        if alpha < 1.0 && target.alpha_mode == AlphaMode::Opaque {
+            target.alpha_mode = AlphaMode::Blend;
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BackgroundColorLens {
+    pub start: Color,
+    pub end: Color,
+}
+
+impl Lens<BackgroundColor> for BackgroundColorLens {
+    fn lerp(&mut self, target: &mut BackgroundColor, ratio: f32) {
+        target.0 = lerp_color(self.start, self.end, ratio);
+    }
+}
+
+/// Like `bevy_tweening::lens::TextColorLens`, but for every section, e.g. the text and
+/// shadow of a text button.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextSectionsColorLens {
+    pub start: Color,
+    pub end: Color,
+}
+
+impl Lens<Text> for TextSectionsColorLens {
+    fn lerp(&mut self, target: &mut Text, ratio: f32) {
+        let color = lerp_color(self.start, self.end, ratio);
+        for section in target.sections.iter_mut() {
+            section.style.color = color;
+        }
+    }
+}
+
+/// Volume of a playing sound. `apply_audio_settings` leaves the sink alone until the
+/// tween completes, so `end` should be the volume from the settings.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AudioVolumeLens {
+    pub start: f32,
+    pub end: f32,
+}
+
+impl Lens<AudioSink> for AudioVolumeLens {
+    fn lerp(&mut self, target: &mut AudioSink, ratio: f32) {
+        target.set_volume(self.start + (self.end - self.start) * ratio);
+    }
+}
+
+/// Gives an entity its own copy of its `StandardMaterial`, so animating it doesn't
+/// affect other entities sharing the same handle.
+#[derive(Component)]
+pub struct UniqueMaterial;
+
+/// Animates the material of one entity, cloning it first unless it's already unique.
+pub struct AnimateMaterial<T> {
+    pub entity: Entity,
+    pub tweenable: T,
+}
+
+impl<T: Tweenable<StandardMaterial> + 'static> Command for AnimateMaterial<T> {
+    fn apply(self, world: &mut World) {
+        let Some(handle) = world.get::<Handle<StandardMaterial>>(self.entity).cloned() else {
+            warn!("{:?} has no material to animate", self.entity);
+            return;
+        };
+
+        let handle = if world.get::<UniqueMaterial>(self.entity).is_some() {
+            handle
+        } else {
+            let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+            let Some(material) = materials.get(&handle).cloned() else {
+                warn!("material of {:?} is not loaded", self.entity);
+                return;
+            };
+            let unique = materials.add(material);
+            world
+                .entity_mut(self.entity)
+                .insert((unique.clone(), UniqueMaterial));
+            unique
+        };
+
+        world
+            .entity_mut(self.entity)
+            .insert(AssetAnimator::new(handle, self.tweenable));
+    }
+}
+
+pub trait MaterialTweenCommands {
+    fn animate_material(
+        &mut self,
+        entity: Entity,
+        tweenable: impl Tweenable<StandardMaterial> + 'static,
+    );
+}
+
+impl<'w, 's> MaterialTweenCommands for Commands<'w, 's> {
+    fn animate_material(
+        &mut self,
+        entity: Entity,
+        tweenable: impl Tweenable<StandardMaterial> + 'static,
+    ) {
+        self.add(AnimateMaterial { entity, tweenable });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_color_eq(actual: Color, expected: Color) {
+        let difference = Vec4::from(actual) - Vec4::from(expected);
+        assert!(
+            difference.abs().max_element() < 1e-5,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn material_colors_lerp() {
+        let mut material = StandardMaterial::default();
+        StandardMaterialBaseColorLens {
+            start: Color::BLACK,
+            end: Color::WHITE,
+        }
+        .lerp(&mut material, 0.25);
+        assert_color_eq(material.base_color, Color::rgb(0.25, 0.25, 0.25));
+
+        StandardMaterialEmissiveLens {
+            start: Color::rgb(1.0, 0.0, 0.0),
+            end: Color::BLACK,
+        }
+        .lerp(&mut material, 0.5);
+        assert_color_eq(material.emissive, Color::rgb(0.5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn alpha_lerps_and_blends_opaque_materials() {
+        let mut material = StandardMaterial::from(Color::RED);
+        let mut lens = StandardMaterialAlphaLens {
+            start: 1.0,
+            end: 0.0,
+        };
+
+        lens.lerp(&mut material, 0.0);
+        assert_eq!(material.alpha_mode, AlphaMode::Opaque);
+        lens.lerp(&mut material, 0.75);
+        assert_color_eq(material.base_color, Color::rgba(1.0, 0.0, 0.0, 0.25));
+        assert_eq!(material.alpha_mode, AlphaMode::Blend);
+
+        // other alpha modes are kept
+        let mut material = StandardMaterial {
+            alpha_mode: AlphaMode::Mask(0.5),
+            ..default()
+        };
+        lens.lerp(&mut material, 0.75);
+        assert_eq!(material.alpha_mode, AlphaMode::Mask(0.5));
+    }
+
+    #[test]
+    fn ui_colors_lerp() {
+        let mut background = BackgroundColor::default();
+        BackgroundColorLens {
+            start: Color::NONE,
+            end: Color::WHITE,
+        }
+        .lerp(&mut background, 0.5);
+        assert_color_eq(background.0, Color::rgba(0.5, 0.5, 0.5, 0.5));
+
+        let style = TextStyle::default();
+        let mut text = Text::from_sections([
+            TextSection::new("text", style.clone()),
+            TextSection::new("shadow", style),
+        ]);
+        TextSectionsColorLens {
+            start: Color::WHITE,
+            end: Color::BLACK,
+        }
+        .lerp(&mut text, 1.0);
+        for section in text.sections.iter() {
+            assert_color_eq(section.style.color, Color::BLACK);
+        }
+    }
+}
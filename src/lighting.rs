@@ -0,0 +1,308 @@
+use bevy::prelude::*;
+use bevy_tweening::{component_animator_system, AnimationSystem, Animator, Lens};
+
+use crate::{
+    settings::{Quality, Settings},
+    transition::TransitionSettings,
+    warmup::ShaderWarmupDone,
+    GameState,
+};
+
+/// A key, fill and rim directional light plus ambient light, set from `LightingPresets`.
+///
+/// Like the post-processing presets, each `GameState` has one, and the rig eases into it
+/// while a screen transition reveals the new state. The presets are reflected, so they
+/// can be tuned live in the world inspector.
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<LightingPresets>()
+            .register_type::<LightingPreset>()
+            .register_type::<LightPreset>()
+            .init_resource::<LightingPresets>()
+            .add_systems(Startup, spawn_light_rig)
+            .add_systems(
+                Update,
+                component_animator_system::<Lighting>.in_set(AnimationSystem::AnimationUpdate),
+            )
+            .add_systems(
+                Update,
+                (
+                    tween_lighting_to_state,
+                    apply_lighting.after(AnimationSystem::AnimationUpdate),
+                ),
+            );
+    }
+}
+
+#[derive(Reflect, Clone, Copy, PartialEq, Debug)]
+pub struct LightPreset {
+    pub color: Color,
+    /// Lux.
+    pub illuminance: f32,
+    /// Where the light comes from, it points from here towards the origin.
+    pub from: Vec3,
+}
+
+impl LightPreset {
+    fn lerp(&self, end: &Self, ratio: f32) -> Self {
+        let start_color: Vec4 = self.color.into();
+        let end_color: Vec4 = end.color.into();
+        Self {
+            color: start_color.lerp(end_color, ratio).into(),
+            illuminance: self.illuminance + (end.illuminance - self.illuminance) * ratio,
+            from: self.from.lerp(end.from, ratio),
+        }
+    }
+}
+
+#[derive(Reflect, Clone, Copy, PartialEq, Debug)]
+pub struct LightingPreset {
+    pub key: LightPreset,
+    pub fill: LightPreset,
+    pub rim: LightPreset,
+    pub ambient_color: Color,
+    pub ambient_brightness: f32,
+    /// Only the key light casts shadows, and only on high quality or during the shader
+    /// warmup, so the shadow pipelines are compiled either way.
+    pub shadows: bool,
+    pub shadow_depth_bias: f32,
+    pub shadow_normal_bias: f32,
+}
+
+impl LightingPreset {
+    /// Shadow settings can't be blended, they switch to `end` right away.
+    pub fn lerp(&self, end: &Self, ratio: f32) -> Self {
+        let start_ambient: Vec4 = self.ambient_color.into();
+        let end_ambient: Vec4 = end.ambient_color.into();
+        Self {
+            key: self.key.lerp(&end.key, ratio),
+            fill: self.fill.lerp(&end.fill, ratio),
+            rim: self.rim.lerp(&end.rim, ratio),
+            ambient_color: start_ambient.lerp(end_ambient, ratio).into(),
+            ambient_brightness: self.ambient_brightness
+                + (end.ambient_brightness - self.ambient_brightness) * ratio,
+            ..*end
+        }
+    }
+}
+
+impl Default for LightingPreset {
+    // tuned for the panda standing at the middle of the screen, facing the camera
+    fn default() -> Self {
+        Self {
+            // warm, from the top right in front
+            key: LightPreset {
+                color: Color::rgb(1.0, 0.95, 0.88),
+                illuminance: 40000.0,
+                from: Vec3::new(0.5, 1.0, 1.0),
+            },
+            // cool and soft, from the left, keeps the shadow side readable
+            fill: LightPreset {
+                color: Color::rgb(0.8, 0.88, 1.0),
+                illuminance: 10000.0,
+                from: Vec3::new(-1.0, 0.3, 1.0),
+            },
+            // from behind, outlines the black fur against the background
+            rim: LightPreset {
+                color: Color::WHITE,
+                illuminance: 15000.0,
+                from: Vec3::new(0.0, 1.0, -1.5),
+            },
+            ambient_color: Color::WHITE,
+            ambient_brightness: 0.1,
+            shadows: true,
+            shadow_depth_bias: DirectionalLight::DEFAULT_SHADOW_DEPTH_BIAS,
+            shadow_normal_bias: DirectionalLight::DEFAULT_SHADOW_NORMAL_BIAS,
+        }
+    }
+}
+
+#[derive(Resource, Reflect, Clone, Copy)]
+#[reflect(Resource)]
+pub struct LightingPresets {
+    pub loading: LightingPreset,
+    pub menu: LightingPreset,
+    pub playing: LightingPreset,
+}
+
+impl Default for LightingPresets {
+    fn default() -> Self {
+        let playing = LightingPreset::default();
+        // moodier, with a stronger rim, for the panda posing behind the play button
+        let menu = LightingPreset {
+            key: LightPreset {
+                illuminance: 30000.0,
+                ..playing.key
+            },
+            fill: LightPreset {
+                illuminance: 6000.0,
+                ..playing.fill
+            },
+            rim: LightPreset {
+                illuminance: 25000.0,
+                ..playing.rim
+            },
+            ambient_brightness: 0.05,
+            ..playing
+        };
+        Self {
+            loading: menu,
+            menu,
+            playing,
+        }
+    }
+}
+
+impl LightingPresets {
+    pub fn for_state(&self, state: &GameState) -> LightingPreset {
+        match state {
+            GameState::Loading => self.loading,
+            GameState::Menu => self.menu,
+            GameState::Playing => self.playing,
+        }
+    }
+}
+
+/// The preset currently applied to the rig.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Lighting(pub LightingPreset);
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LightRole {
+    Key,
+    Fill,
+    Rim,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LightingLens {
+    pub start: LightingPreset,
+    pub end: LightingPreset,
+}
+
+impl Lens<Lighting> for LightingLens {
+    fn lerp(&mut self, target: &mut Lighting, ratio: f32) {
+        target.0 = self.start.lerp(&self.end, ratio);
+    }
+}
+
+// spawned before loading, so the warmup compiles the lit and shadow pipelines
+fn spawn_light_rig(mut commands: Commands, presets: Res<LightingPresets>) {
+    commands
+        .spawn((Lighting(presets.loading), SpatialBundle::default()))
+        .with_children(|parent| {
+            for role in [LightRole::Key, LightRole::Fill, LightRole::Rim] {
+                parent.spawn((role, DirectionalLightBundle::default()));
+            }
+        });
+}
+
+fn tween_lighting_to_state(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    presets: Res<LightingPresets>,
+    transition_settings: Res<TransitionSettings>,
+    q_rig: Query<(Entity, &Lighting)>,
+) {
+    if !state.is_changed() && !presets.is_changed() {
+        return;
+    }
+
+    let target = presets.for_state(state.get());
+    for (entity, lighting) in q_rig.iter() {
+        commands
+            .entity(entity)
+            .insert(Animator::new(transition_settings.reveal_tween(
+                LightingLens {
+                    start: lighting.0,
+                    end: target,
+                },
+            )));
+    }
+}
+
+fn apply_lighting(
+    settings: Res<Settings>,
+    warmup_done: Res<ShaderWarmupDone>,
+    mut ambient_light: ResMut<AmbientLight>,
+    q_rig: Query<(Ref<Lighting>, &Children)>,
+    mut q_light: Query<(&LightRole, &mut DirectionalLight, &mut Transform)>,
+) {
+    for (lighting, children) in q_rig.iter() {
+        if !lighting.is_changed() && !settings.is_changed() && !warmup_done.is_changed() {
+            continue;
+        }
+        let preset = &lighting.0;
+        let shadows = preset.shadows && (settings.quality == Quality::High || !warmup_done.0);
+
+        ambient_light.color = preset.ambient_color;
+        ambient_light.brightness = preset.ambient_brightness;
+
+        let mut lights = q_light.iter_many_mut(children);
+        while let Some((role, mut light, mut transform)) = lights.fetch_next() {
+            let light_preset = match role {
+                LightRole::Key => &preset.key,
+                LightRole::Fill => &preset.fill,
+                LightRole::Rim => &preset.rim,
+            };
+            light.color = light_preset.color;
+            light.illuminance = light_preset.illuminance;
+            light.shadows_enabled = *role == LightRole::Key && shadows;
+            light.shadow_depth_bias = preset.shadow_depth_bias;
+            light.shadow_normal_bias = preset.shadow_normal_bias;
+            *transform =
+                Transform::from_translation(light_preset.from).looking_at(Vec3::ZERO, Vec3::Y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dark() -> LightingPreset {
+        let light = LightPreset {
+            color: Color::BLACK,
+            illuminance: 0.0,
+            from: Vec3::new(-1.0, 0.0, 1.0),
+        };
+        LightingPreset {
+            key: light,
+            fill: light,
+            rim: light,
+            ambient_color: Color::BLACK,
+            ambient_brightness: 0.0,
+            shadows: false,
+            shadow_depth_bias: 0.0,
+            shadow_normal_bias: 0.0,
+        }
+    }
+
+    #[test]
+    fn lerp_blends_lights_and_ambient() {
+        let start = dark();
+        let end = LightingPreset::default();
+        assert_eq!(start.lerp(&end, 0.0).key, start.key);
+        assert_eq!(start.lerp(&end, 1.0), end);
+
+        let halfway = start.lerp(&end, 0.5);
+        assert_eq!(halfway.key.illuminance, end.key.illuminance / 2.0);
+        assert_eq!(halfway.fill.illuminance, end.fill.illuminance / 2.0);
+        assert_eq!(halfway.rim.color, Color::rgb(0.5, 0.5, 0.5));
+        assert_eq!(halfway.rim.from, Vec3::new(-0.5, 0.5, -0.25));
+        assert_eq!(halfway.ambient_color, Color::rgb(0.5, 0.5, 0.5));
+        assert_eq!(halfway.ambient_brightness, end.ambient_brightness / 2.0);
+    }
+
+    #[test]
+    fn shadow_settings_switch_right_away() {
+        let start = dark();
+        let end = LightingPreset::default();
+        let blended = start.lerp(&end, 0.01);
+        assert!(blended.shadows);
+        assert_eq!(blended.shadow_depth_bias, end.shadow_depth_bias);
+        assert_eq!(blended.shadow_normal_bias, end.shadow_normal_bias);
+    }
+}
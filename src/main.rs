@@ -4,36 +4,103 @@
 use bevy::{
     math::vec3,
     prelude::*,
-    render::{camera::ScalingMode, mesh::Indices, render_resource::PrimitiveTopology},
-    window::{PrimaryWindow, WindowResized}, input::keyboard,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    input::keyboard,
 };
 #[cfg(feature = "inspector")]
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_mod_picking::prelude::*;
-use bevy_tweening::{lens::*, *};
-// use button::interact_button;
+use bevy_tweening::*;
+use animation::{
+    AnimationLibrary, AnimationLibraryPlugin, AnimationStateMachine, AnimationTrigger,
+    CharacterAnimation,
+};
+use background::{
+    BackgroundMaterial, BackgroundPlugin, BackgroundSettings, BACKGROUND_SHADER_PATH,
+};
+use button::{interact_button, tick_button_cooldowns, ButtonCooldown};
+use camera::{CameraRig, CameraRigPlugin, CameraScaling, VisibleWorldRect};
+use camera_effects::{CameraEffects, CameraEffectsPlugin, CameraFollow, CameraShake, ZoomPunch};
+use character::{CharacterBumped, CharacterController, CharacterPlugin};
 use default_font::{DefaultFont, DefaultFontPlugin};
+use focus::{FocusActivated, FocusPlugin, Focusable, Focused, PickActivated};
+use lenses::{MaterialTweenCommands, StandardMaterialAlphaLens, StandardMaterialEmissiveLens};
+use particles::{ParticleConfig, ParticleEmitter, ParticleEmitterBundle, ParticlesPlugin};
+use lighting::LightingPlugin;
+use pause::PausePlugin;
+use post_process::{PostProcess, PostProcessPlugin};
+use scene_ready::{SceneMaterialOverride, SceneReadyPlugin, SceneShadows};
+use settings::{SettingsPlugin, SettingsState};
+use theme::{Palette, ThemePlugin};
+use timestep::{FixedTimestepPlugin, InterpolatedTransformBundle};
+use toon::{ToonPlugin, ToonShading};
+use transition::{TransitionPlugin, TransitionTo};
+use tween::{
+    parallel, pop_in, sequence, slide_in, spin_in, wait, zoom_in, TweenCallbacks,
+    TweenHelpersPlugin,
+};
 use framerate::{FramerateIsStable, FramerateMonitorPlugin};
 // use text_mesh::text_to_mesh;
-use crate::text_to_image::text_to_image;
+use crate::text_to_image::{text_to_image, TextTexture};
 use std::{f32::consts::PI, time::Duration};
 use utils::*;
+use warmup::{warmup_transform, ShaderWarmup, ShaderWarmupDone, ShaderWarmupPlugin};
+use widgets::WidgetsPlugin;
 #[cfg(target_arch = "wasm32")]
 use web_event::send_loaded_event;
 
+mod animation;
+mod background;
 mod button;
+mod camera;
+mod camera_effects;
+mod character;
 mod default_font;
+mod focus;
 mod framerate;
+mod lenses;
+mod lighting;
 mod mute;
+mod particles;
+mod pause;
+mod post_process;
+mod scene_ready;
+mod settings;
 mod text_to_image;
+mod theme;
+mod timestep;
+mod toon;
+mod transition;
+mod tween;
 mod utils;
+mod warmup;
+mod widgets;
 #[cfg(target_arch = "wasm32")]
 mod web_event;
 
 static PRIMARY_COLOR_HUE: f32 = 0.5;
+// the toon bands already shade it, self shadows only add acne
+static OTTER_SHADOWS: SceneShadows = SceneShadows {
+    cast: true,
+    receive: false,
+};
 // static MENU_MUSIC_VOLUME: f32 = 0.36;
 // static PLAYING_MUSIC_VOLUME: f32 = 0.66;
 static WINDOW_WORLD_HEIGHT: f32 = 10.0;
+/// Menu buttons ignore input for this long after the menu appears.
+static MENU_BUTTON_COOLDOWN_SECS: f32 = 1.0;
+/// Camera shake trauma per world unit per second of a `CharacterBumped`.
+static BUMP_TRAUMA_PER_SPEED: f32 = 0.1;
+/// The play button flashes and fades out within the first half of the screen transition.
+static PLAY_BUTTON_FADE_SECS: f32 = 0.25;
+/// Relative to the background plane's parent.
+static PLAY_BUTTON_POSITION: Vec3 = Vec3::new(0.0, 0.0, -5.0);
+/// How far the camera may follow the otter from the origin while playing; the play field
+/// moves along with it.
+static CAMERA_FOLLOW_BOUNDS: Rect = Rect {
+    min: Vec2::new(-2.0, -1.0),
+    max: Vec2::new(2.0, 1.0),
+};
 
 fn main() {
     let mut app = App::new();
@@ -41,8 +108,7 @@ fn main() {
     let default_plugins = DefaultPlugins.set(WindowPlugin {
         primary_window: Some(Window {
             title: "Bevy Game".to_owned(),
-            // mode: WindowMode::Fullscreen,
-            // present_mode: PresentMode::AutoNoVsync,
+            // mode and present_mode are applied from Settings
             canvas: Some("#bevy".to_owned()),
             fit_canvas_to_parent: true,
             prevent_default_event_handling: false,
@@ -55,9 +121,30 @@ fn main() {
     // default_plugins.set(AssetPlugin::processed_dev());
 
     app.add_plugins(default_plugins);
-    // app.add_plugins(DefaultPickingPlugins);
+    app.add_plugins(DefaultPickingPlugins);
     app.add_plugins(TweeningPlugin);
+    app.add_plugins(TweenHelpersPlugin);
+    app.add_plugins(SceneReadyPlugin);
+    app.add_plugins(AnimationLibraryPlugin);
+    app.add_plugins(FocusPlugin);
+    app.add_plugins(PausePlugin);
+    app.add_plugins(ThemePlugin);
+    app.add_plugins(BackgroundPlugin);
+    app.add_plugins(WidgetsPlugin);
+    app.add_plugins(SettingsPlugin);
+    app.add_plugins(TransitionPlugin);
+    app.add_plugins(PostProcessPlugin);
+    app.add_plugins(LightingPlugin);
+    app.add_plugins(ToonPlugin);
+    app.add_plugins(FixedTimestepPlugin {
+        steps_per_second: 60.0,
+    });
+    app.add_plugins(CameraRigPlugin);
+    app.add_plugins(CameraEffectsPlugin);
+    app.add_plugins(CharacterPlugin);
+    app.add_plugins(ParticlesPlugin);
     app.add_plugins(FramerateMonitorPlugin);
+    app.add_plugins(ShaderWarmupPlugin);
     app.add_plugins(DefaultFontPlugin {
         font_path: "Nunito-Regular.ttf",
     });
@@ -72,9 +159,15 @@ fn main() {
     );
     app.add_systems(
         OnEnter(GameState::Loading),
-        (pre_load_setup, load_assets).chain(),
+        (
+            pre_load_setup,
+            load_assets,
+            apply_deferred,
+            spawn_shader_warmup,
+        )
+            .chain(),
     );
-    // app.add_systems(Update, interact_button);
+    app.add_systems(Update, (tick_button_cooldowns, interact_button).chain());
     app.add_systems(
         OnExit(GameState::Loading),
         (spawn_background, apply_deferred, spawn_menu_buttons).chain(),
@@ -89,10 +182,25 @@ fn main() {
     //     Update,
     //     (interact_play_button,).run_if(in_state(GameState::Menu)),
     // );
+    app.add_systems(
+        Update,
+        (activate_play_button,).run_if(in_state(GameState::Menu)),
+    );
+    app.add_systems(OnEnter(GameState::Menu), show_menu_buttons);
+    app.add_systems(
+        OnEnter(GameState::Playing),
+        (hide_menu_buttons, follow_otter),
+    );
+    app.add_systems(OnExit(GameState::Playing), stop_following);
     app.add_systems(Update, resize_background_plane);
+    app.add_systems(Update, (bump_effects, kick_up_dust));
 
+    // in game, escape pauses instead
     #[cfg(not(target_arch = "wasm32"))]
-    app.add_systems(Update, exit_on_esc);
+    app.add_systems(
+        Update,
+        exit_on_esc.run_if(in_state(GameState::Menu).and_then(in_state(SettingsState::Closed))),
+    );
 
     #[cfg(target_arch = "wasm32")]
     app.add_systems(OnExit(GameState::Loading), send_loaded_event);
@@ -120,35 +228,45 @@ struct Music;
 
 fn pre_load_setup(mut commands: Commands) {
     // spawn camera
-    commands.spawn(Camera3dBundle {
-        transform: Transform::from_xyz(0.0, 0.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
-        projection: Projection::Orthographic(OrthographicProjection {
-            scaling_mode: ScalingMode::FixedVertical(WINDOW_WORLD_HEIGHT),
-            scale: 1.0,
+    let camera_rig = CameraRig {
+        scaling: CameraScaling::FixedVertical(WINDOW_WORLD_HEIGHT),
+    };
+    commands.spawn((
+        Camera3dBundle {
+            transform: Transform::from_xyz(0.0, 0.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+            projection: Projection::Orthographic(OrthographicProjection {
+                scaling_mode: camera_rig.scaling.scaling_mode(),
+                scale: 1.0,
+                ..default()
+            }),
             ..default()
-        }),
-        ..default()
-    });
+        },
+        camera_rig,
+        CameraEffects::with_seed(rand::random()),
+        PostProcess::default(),
+        RaycastPickCamera::default(),
+    ));
 }
 
-fn background_scale_from_window_size(width: f32, height: f32) -> Vec3 {
-    let aspect_ratio = width / height;
+fn background_scale_from_visible_size(visible_size: Vec2) -> Vec3 {
     let margin = 0.04;
-    let new_plane_height = WINDOW_WORLD_HEIGHT + margin; // the camera is already scaled to this height
-    let new_plane_width = new_plane_height * aspect_ratio + margin;
+    let new_plane_height = visible_size.y + margin;
+    let new_plane_width = visible_size.x + margin;
     Vec3::new(new_plane_height, 1.0, new_plane_width)
 }
 
 fn resize_background_plane(
-    mut resize_reader: EventReader<WindowResized>,
+    world_rect: Res<VisibleWorldRect>,
     mut q: Query<&mut Transform, With<BackgroundPlane>>,
 ) {
     // update BackgroundPlane scale to fill window
-    for event in resize_reader.iter() {
-        for mut transform in q.iter_mut() {
-            // info!("window resized: {}x{}", event.width, event.height);
-            transform.scale = background_scale_from_window_size(event.width, event.height);
-        }
+    if !world_rect.is_changed() {
+        return;
+    }
+    for mut transform in q.iter_mut() {
+        transform.scale = background_scale_from_visible_size(world_rect.rect.size());
+        // stay behind the camera while it follows the otter
+        transform.translation = world_rect.rect.center().extend(transform.translation.z);
     }
 }
 
@@ -199,19 +317,31 @@ fn button_mesh(shape: shape::Box) -> Mesh {
 #[derive(Component)]
 struct Otter;
 
+/// Kicked up by the otter while it runs.
+#[derive(Component)]
+struct Dust;
+
 fn load_assets(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut images: ResMut<Assets<Image>>,
     mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    mut animation_libraries: ResMut<Assets<AnimationLibrary>>,
+    mut background_materials: ResMut<Assets<BackgroundMaterial>>,
+    palette: Res<Palette>,
+    background_settings: Res<BackgroundSettings>,
 ) {
     commands.insert_resource(AssetHandle::<Otter, Scene>::new(
         asset_server.load("panda.gltf#Scene0"),
     ));
 
-    commands.insert_resource(AssetHandle::<Otter, AnimationClip>::new(
-        asset_server.load("panda.gltf#Animation3")
+    // clips: hehu, idle.001, running, running.001, start_run, tpose
+    commands.insert_resource(AssetHandle::<Otter, AnimationLibrary>::new(
+        animation_libraries.add(
+            AnimationLibrary::new(asset_server.load("panda.gltf"))
+                .require(CharacterAnimation::clip_names()),
+        ),
     ));
 
     commands.insert_resource(AssetHandle::<DefaultFont, Font>::new(
@@ -223,7 +353,7 @@ fn load_assets(
     ));
     commands.insert_resource(AssetHandle::<PlayButton, StandardMaterial>::new(
         standard_materials.add(StandardMaterial {
-            base_color_texture: Some(images.add(text_to_image("Play"))),
+            base_color_texture: Some(images.add(text_to_image("Play", palette.primary))),
             ..default()
         }),
     ));
@@ -236,30 +366,140 @@ fn load_assets(
     )));
     commands.insert_resource(AssetHandle::<BackgroundPlane, StandardMaterial>::new(
         standard_materials.add(StandardMaterial {
-            base_color: Color::hsl(PRIMARY_COLOR_HUE * 360.0, 0.2, 0.2),
+            base_color: palette.background,
             unlit: true,
             ..default()
         }),
     ));
+    // falls back to the flat material above if the shader doesn't load
+    commands.insert_resource(AssetHandle::<BackgroundPlane, Shader>::new(
+        asset_server.load(BACKGROUND_SHADER_PATH),
+    ));
+    commands.insert_resource(AssetHandle::<BackgroundPlane, BackgroundMaterial>::new(
+        background_materials.add(BackgroundMaterial::new(&palette, &background_settings)),
+    ));
+}
 
-    // pbr precompilation mesh, to avoid lag spike when spawning first pbr object
-    commands.spawn(PbrBundle {
-        mesh: meshes.add(Mesh::from(shape::Plane {
-            size: 1.0,
-            subdivisions: 0,
+/// One entity per material and mesh layout we draw, so their pipelines are compiled
+/// before leaving the loading screen.
+fn spawn_shader_warmup(
+    mut commands: Commands,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+    otter_scene: Res<AssetHandle<Otter, Scene>>,
+    play_button_mesh: Res<AssetHandle<PlayButton, Mesh>>,
+    play_button_material: Res<AssetHandle<PlayButton, StandardMaterial>>,
+    background_plane_mesh: Res<AssetHandle<BackgroundPlane, Mesh>>,
+    background_plane_material: Res<AssetHandle<BackgroundPlane, StandardMaterial>>,
+    background_material: Res<AssetHandle<BackgroundPlane, BackgroundMaterial>>,
+    particle_mesh: Res<AssetHandle<ParticleEmitter, Mesh>>,
+) {
+    // skinned, with toon shading and outlines
+    commands.spawn((
+        ShaderWarmup,
+        ToonShading::default(),
+        OTTER_SHADOWS,
+        SceneBundle {
+            scene: otter_scene.handle.clone(),
+            transform: warmup_transform(),
+            ..default()
+        },
+    ));
+    // skinned and lit, for when toon shading is off. Textures don't change the pipeline,
+    // so a plain material with the same culling stands in for the panda's
+    commands.spawn((
+        ShaderWarmup,
+        OTTER_SHADOWS,
+        SceneMaterialOverride(standard_materials.add(StandardMaterial {
+            double_sided: true,
+            cull_mode: None,
+            ..Color::WHITE.into()
         })),
-        material: standard_materials.add(Color::RED.into()),
-        ..default()
-    });
+        SceneBundle {
+            scene: otter_scene.handle.clone(),
+            transform: warmup_transform(),
+            ..default()
+        },
+    ));
+    // text textured, and blended once it has faded out
+    let play_button_blended = standard_materials
+        .get(&play_button_material.handle)
+        .map(|material| StandardMaterial {
+            alpha_mode: AlphaMode::Blend,
+            ..material.clone()
+        })
+        .unwrap_or_default();
+    for material in [
+        play_button_material.handle.clone(),
+        standard_materials.add(play_button_blended),
+    ] {
+        commands.spawn((
+            ShaderWarmup,
+            PbrBundle {
+                mesh: play_button_mesh.handle.clone(),
+                material,
+                transform: warmup_transform(),
+                ..default()
+            },
+        ));
+    }
+    // unlit, and the procedural background
+    commands.spawn((
+        ShaderWarmup,
+        PbrBundle {
+            mesh: background_plane_mesh.handle.clone(),
+            material: background_plane_material.handle.clone(),
+            transform: warmup_transform(),
+            ..default()
+        },
+    ));
+    commands.spawn((
+        ShaderWarmup,
+        MaterialMeshBundle {
+            mesh: background_plane_mesh.handle.clone(),
+            material: background_material.handle.clone(),
+            transform: warmup_transform(),
+            ..default()
+        },
+    ));
+    // lit
+    commands.spawn((
+        ShaderWarmup,
+        PbrBundle {
+            mesh: background_plane_mesh.handle.clone(),
+            material: standard_materials.add(Color::WHITE.into()),
+            transform: warmup_transform(),
+            ..default()
+        },
+    ));
+    // particles
+    commands.spawn((
+        ShaderWarmup,
+        PbrBundle {
+            mesh: particle_mesh.handle.clone(),
+            material: standard_materials.add(StandardMaterial {
+                unlit: true,
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            }),
+            transform: warmup_transform(),
+            ..default()
+        },
+    ));
 }
 
 fn wait_for_loading(
     mut next_state: ResMut<NextState<GameState>>,
     asset_server: Res<AssetServer>,
     font_handle: Res<AssetHandle<DefaultFont, Font>>,
+    otter_animations: Res<AssetHandle<Otter, AnimationLibrary>>,
+    animation_libraries: Res<Assets<AnimationLibrary>>,
     framerate_stable: Res<FramerateIsStable>,
+    shader_warmup: Res<ShaderWarmupDone>,
 ) {
-    let mut all_loaded = true;
+    // a failed library has logged its error, the game goes on without animations
+    let mut all_loaded = animation_libraries
+        .get(&otter_animations.handle)
+        .map_or(false, |library| library.is_settled());
 
     let mut handles: Vec<HandleUntyped> = vec![];
     handles.push(font_handle.handle.clone().into());
@@ -276,7 +516,7 @@ fn wait_for_loading(
         }
     }
 
-    if all_loaded && framerate_stable.0 {
+    if all_loaded && framerate_stable.0 && shader_warmup.0 {
         info!("All assets loaded, entering menu state");
         next_state.set(GameState::Menu);
     }
@@ -286,33 +526,22 @@ fn spawn_background(
     mut commands: Commands,
     background_plane_material: Res<AssetHandle<BackgroundPlane, StandardMaterial>>,
     background_plane_mesh: Res<AssetHandle<BackgroundPlane, Mesh>>,
-    q_window: Query<&Window, With<PrimaryWindow>>,
+    world_rect: Res<VisibleWorldRect>,
+    mut tween_callbacks: ResMut<TweenCallbacks>,
 ) {
-    let window = q_window.single();
-
-    let background_tween_scale = Tween::new(
-        EaseFunction::ExponentialOut,
-        Duration::from_secs_f32(1.7 * 0.5),
-        TransformScaleLens {
-            start: Vec3::splat(0.0001),
-            end: Vec3::splat(1.0),
-        },
-    );
-
-    let background_tween_rot = Tween::new(
-        EaseFunction::ExponentialOut,
-        Duration::from_secs_f32(1.1 * 0.5),
-        TransformRotationLens {
-            start: Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, -PI / 2.0),
-            end: Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, 0.0),
-        },
-    );
+    // a little bump as it settles
+    let landed = tween_callbacks.send(|_| ZoomPunch { amount: 0.03 });
+    let background_intro = parallel([
+        zoom_in(1.7 * 0.5).with_completed_event(landed),
+        spin_in(
+            Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, -PI / 2.0),
+            Quat::IDENTITY,
+            1.1 * 0.5,
+        ),
+    ]);
 
     commands
-        .spawn((
-            SpatialBundle::default(),
-            Animator::new(Tracks::new([background_tween_scale, background_tween_rot])),
-        ))
+        .spawn((SpatialBundle::default(), Animator::new(background_intro)))
         .with_children(|parent| {
             parent.spawn((
                 BackgroundPlane,
@@ -321,9 +550,8 @@ fn spawn_background(
                     material: background_plane_material.handle.clone(),
                     transform: Transform::from_translation(vec3(0.0, 0.0, -10.0))
                         .with_rotation(Quat::from_euler(EulerRot::XYZ, 0.0, PI * 0.5, PI * 0.5))
-                        .with_scale(background_scale_from_window_size(
-                            window.width(),
-                            window.height(),
+                        .with_scale(background_scale_from_visible_size(
+                            world_rect.rect.size(),
                         )),
                     ..default()
                 },
@@ -331,54 +559,18 @@ fn spawn_background(
         });
 }
 
-// impl Tweenable for Duration {
-//     fn tween(&self, _ease: EaseFunction, _elapsed: Duration, _duration: Duration) -> Self {
-//         *self
-//     }
-// }
-
-// #[derive(Component)]
-// struct Dummy;
-
-// impl Tweenable< for Dummy {
-//     fn tween(&self, _ease: EaseFunction, _elapsed: Duration, _duration: Duration) -> Self {
-//         *self
-//     }
-// }
-
-// #[derive(Debug, Copy, Clone, PartialEq)]
-// struct DummyLens;
-
-// impl Lens<Dummy> for DummyLens {
-//     fn lerp(&mut self, _: &mut Dummy, _: f32) {}
-// }
-
-// fn tween_wait(seconds: f32) -> Tween<Dummy> {
-//     Tween::new(
-//         EaseFunction::QuadraticIn,
-//         Duration::from_secs_f32(seconds),
-//         DummyLens,
-//     )
-// }
-
 fn spawn_menu_buttons(
     mut commands: Commands,
     play_button_material: Res<AssetHandle<PlayButton, StandardMaterial>>,
     play_button_mesh: Res<AssetHandle<PlayButton, Mesh>>,
     q_background_plane_parent: Query<&Parent, With<BackgroundPlane>>,
+    mut tween_callbacks: ResMut<TweenCallbacks>,
 ) {
     let background_plane_parent = q_background_plane_parent.single();
 
-    let tween_scale = Tween::new(
-        EaseFunction::BounceOut,
-        Duration::from_secs_f32(0.8),
-        TransformScaleLens {
-            start: Vec3::splat(0.0),
-            end: Vec3::splat(1.0),
-        },
-    );
-
-    let animator = Animator::new(Delay::new(Duration::from_secs_f32(0.2)).then(tween_scale));
+    // focus the button once it's fully visible, so a gamepad can start right away
+    let popped_in = tween_callbacks.run(focus_play_button);
+    let animator = Animator::new(wait(0.2).then(pop_in(0.8).with_completed_event(popped_in)));
 
     commands
         .entity(background_plane_parent.get())
@@ -386,10 +578,15 @@ fn spawn_menu_buttons(
             parent.spawn((
                 animator,
                 PlayButton,
+                TextTexture("Play".to_owned()),
+                Focusable { order: 0 },
+                PickableBundle::default(),
+                RaycastPickTarget::default(),
+                On::<Pointer<Click>>::send_event::<PickActivated>(),
                 PbrBundle {
                     mesh: play_button_mesh.handle.clone(),
                     material: play_button_material.handle.clone(),
-                    transform: Transform::from_translation(vec3(0.0, 0.0, -5.0))
+                    transform: Transform::from_translation(PLAY_BUTTON_POSITION)
                         .with_rotation(Quat::from_euler(EulerRot::XYZ, PI * 0.75, PI, PI * 1.125))
                         .with_scale(Vec3::splat(0.0)),
                     ..default()
@@ -398,103 +595,240 @@ fn spawn_menu_buttons(
         });
 }
 
-// fn setup_scene_once_loaded(
-//     mut players: Query<&mut AnimationPlayer>,
-//     otter_animation: Res<AssetHandle<Otter, AnimationClip>>,
-// ) {
-//     for mut player in &mut players {
-//         if player.is_paused() {
-//             player.play(otter_animation.handle.clone()).repeat();
-//         }
-//     }
-// }
+fn focus_play_button(In(entity): In<Entity>, mut focused: ResMut<Focused>) {
+    if focused.0.is_none() {
+        focused.0 = Some(entity);
+    }
+}
+
+fn activate_play_button(
+    mut commands: Commands,
+    mut activated: EventReader<FocusActivated>,
+    palette: Res<Palette>,
+    q_play_button: Query<&GlobalTransform, With<PlayButton>>,
+    mut zoom_punch: EventWriter<ZoomPunch>,
+    mut transition: EventWriter<TransitionTo>,
+) {
+    for event in activated.iter() {
+        if let Ok(button_transform) = q_play_button.get(event.0) {
+            // in front of the button, so it doesn't hide the confetti
+            let position = button_transform.translation().truncate().extend(-1.0);
+            let colors = vec![palette.primary, palette.secondary, palette.accent];
+            commands.spawn(ParticleEmitterBundle::new(
+                ParticleEmitter::burst(ParticleConfig::confetti(colors), 60, rand::random()),
+                position,
+            ));
+            let flash = sequence([
+                Tween::new(
+                    EaseFunction::QuadraticOut,
+                    Duration::from_secs_f32(PLAY_BUTTON_FADE_SECS * 0.3),
+                    StandardMaterialEmissiveLens {
+                        start: Color::BLACK,
+                        end: palette.accent,
+                    },
+                ),
+                Tween::new(
+                    EaseFunction::QuadraticIn,
+                    Duration::from_secs_f32(PLAY_BUTTON_FADE_SECS * 0.7),
+                    StandardMaterialEmissiveLens {
+                        start: palette.accent,
+                        end: Color::BLACK,
+                    },
+                ),
+            ]);
+            commands.animate_material(
+                event.0,
+                parallel([
+                    BoxedTweenable::from(flash),
+                    Tween::new(
+                        EaseFunction::QuadraticIn,
+                        Duration::from_secs_f32(PLAY_BUTTON_FADE_SECS),
+                        StandardMaterialAlphaLens {
+                            start: 1.0,
+                            end: 0.0,
+                        },
+                    )
+                    .into(),
+                ]),
+            );
+            zoom_punch.send(ZoomPunch { amount: 0.1 });
+            transition.send(TransitionTo(GameState::Playing));
+        }
+    }
+}
+
+fn bump_effects(
+    mut commands: Commands,
+    mut bumped: EventReader<CharacterBumped>,
+    mut shake: EventWriter<CameraShake>,
+    palette: Res<Palette>,
+) {
+    for event in bumped.iter() {
+        shake.send(CameraShake {
+            trauma: (event.speed * BUMP_TRAUMA_PER_SPEED).min(0.5),
+        });
+        commands.spawn(ParticleEmitterBundle::new(
+            ParticleEmitter::burst(ParticleConfig::sparks(palette.accent), 24, rand::random()),
+            event.position.truncate().extend(-1.0),
+        ));
+    }
+}
+
+fn kick_up_dust(
+    q_otter: Query<(&Transform, &CharacterController), With<Otter>>,
+    mut q_dust: Query<(&mut Transform, &mut ParticleEmitter), (With<Dust>, Without<Otter>)>,
+) {
+    let Ok((otter_transform, controller)) = q_otter.get_single() else {
+        return;
+    };
+    for (mut transform, mut emitter) in q_dust.iter_mut() {
+        // behind the otter, particles move along with it
+        transform.translation = otter_transform.translation.truncate().extend(-3.5);
+        emitter.paused = !controller.is_moving();
+    }
+}
+
+fn show_menu_buttons(
+    mut commands: Commands,
+    standard_materials: Res<Assets<StandardMaterial>>,
+    mut q_play_button: Query<
+        (Entity, &mut Visibility, &Handle<StandardMaterial>),
+        With<PlayButton>,
+    >,
+) {
+    for (entity, mut visibility, material_handle) in q_play_button.iter_mut() {
+        *visibility = Visibility::Inherited;
+        // faded out when it was activated
+        let alpha = standard_materials
+            .get(material_handle)
+            .map_or(1.0, |material| material.base_color.a());
+        if alpha < 1.0 {
+            // and rises back into place
+            commands.entity(entity).insert(Animator::new(slide_in(
+                PLAY_BUTTON_POSITION - Vec3::Y * 0.5,
+                PLAY_BUTTON_POSITION,
+                PLAY_BUTTON_FADE_SECS * 2.0,
+            )));
+            commands.animate_material(
+                entity,
+                Tween::new(
+                    EaseFunction::QuadraticOut,
+                    Duration::from_secs_f32(PLAY_BUTTON_FADE_SECS),
+                    StandardMaterialAlphaLens {
+                        start: alpha,
+                        end: 1.0,
+                    },
+                ),
+            );
+        }
+        // ignore clicks that were meant for whatever was on screen before
+        commands
+            .entity(entity)
+            .insert(ButtonCooldown::from_secs(MENU_BUTTON_COOLDOWN_SECS));
+    }
+}
+
+fn hide_menu_buttons(mut q_play_button: Query<&mut Visibility, With<PlayButton>>) {
+    for mut visibility in q_play_button.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn follow_otter(
+    mut commands: Commands,
+    q_otter: Query<Entity, With<Otter>>,
+    q_camera: Query<Entity, With<CameraEffects>>,
+) {
+    let Ok(otter) = q_otter.get_single() else {
+        return;
+    };
+    for camera in q_camera.iter() {
+        commands.entity(camera).insert(CameraFollow {
+            bounds: Some(CAMERA_FOLLOW_BOUNDS),
+            ..CameraFollow::new(otter)
+        });
+    }
+}
+
+// happens while the transition covers the screen, so the jump back doesn't show
+fn stop_following(
+    mut commands: Commands,
+    mut q_camera: Query<(Entity, &mut CameraEffects), With<CameraFollow>>,
+) {
+    for (camera, mut effects) in q_camera.iter_mut() {
+        commands.entity(camera).remove::<CameraFollow>();
+        // the menu is laid out around the origin
+        effects.anchor = Some(Vec2::ZERO);
+    }
+}
 
 fn keyboard_animation_control(
     keyboard_input: Res<Input<KeyCode>>,
-    mut animation_players: Query<&mut AnimationPlayer>,
-    otter_animation: Res<AssetHandle<Otter, AnimationClip>>,
+    q_otter: Query<Entity, With<Otter>>,
+    mut triggers: EventWriter<AnimationTrigger>,
 ) {
-    for mut player in &mut animation_players {
+    for otter in q_otter.iter() {
         if keyboard_input.just_pressed(KeyCode::Space) {
-            player.play(otter_animation.handle.clone()).repeat();
-
-            info!("play!")
-
-            // if player.is_paused() {
-            //     player.resume();
-            // } else {
-            //     player.pause();
-            // }
+            triggers.send(AnimationTrigger {
+                entity: otter,
+                state: CharacterAnimation::Emote,
+            });
         }
     }
 }
 
 fn setup(
     mut commands: Commands,
+    asset_server: Res<AssetServer>,
     otter_scene: Res<AssetHandle<Otter, Scene>>,
+    otter_animations: Res<AssetHandle<Otter, AnimationLibrary>>,
 ) {
     info!("setup()");
 
-
+    let otter_transform = Transform::from_xyz(0.0, 1.0, -3.0).with_rotation(Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, 0.0)).with_scale(Vec3::splat(3.0));
     commands.spawn((
         Otter,
         SceneBundle {
             scene: otter_scene.handle.clone(),
-            transform: Transform::from_xyz(0.0, 1.0, -3.0).with_rotation(Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, 0.0)).with_scale(Vec3::splat(3.0)),
+            transform: otter_transform,
             ..default()
-        }
+        },
+        otter_animations.handle.clone(),
+        ToonShading::default(),
+        OTTER_SHADOWS,
+        AnimationStateMachine::default(),
+        CharacterController::default(),
+        InterpolatedTransformBundle::new(otter_transform),
     ));
-    
 
-
-    commands.spawn(DirectionalLightBundle {
-        transform: Transform::from_xyz(0.5, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
-        directional_light: DirectionalLight {
-            color: Color::WHITE,
-            illuminance: 50000.0,
-            ..default()
-        },
+    let dust = ParticleConfig {
+        max_particles: 24,
+        lifetime: (0.3, 0.6),
+        speed: (0.5, 1.0),
+        spread: 0.8,
+        gravity: Vec3::new(0.0, -1.0, 0.0),
+        drag: 2.0,
+        colors: vec![Color::WHITE.with_a(0.5)],
+        end_color: Some(Color::WHITE.with_a(0.0)),
+        start_size: 0.1,
+        end_size: 0.25,
         ..default()
-    });
+    };
+    let mut dust = ParticleEmitter::continuous(dust, 15.0, rand::random());
+    dust.paused = true;
+    commands.spawn((
+        Dust,
+        ParticleEmitterBundle::new(dust, otter_transform.translation),
+    ));
 
-    // // spawn otter
-    // commands.spawn((
-    //     Otter,
-    //     otter_scene,
-    // ));
-
-    // // music
-    // commands.spawn((
-    //     AudioBundle {
-    //         source: asset_server.load("music.ogg"),
-    //         settings: PlaybackSettings {
-    //             mode: PlaybackMode::Loop,
-    //             volume: Volume::Relative(VolumeLevel::new(MENU_MUSIC_VOLUME)),
-    //             ..default()
-    //         },
-    //         ..default()
-    //     },
-    //     Music,
-    // ));
-
-    // // spawn score text
-    // commands.spawn((
-    //     ScoreText,
-    //     TextBundle::from_section(
-    //         format!("Score: 0"),
-    //         TextStyle {
-    //             font_size: 64.0,
-    //             color: Color::WHITE,
-    //             ..default()
-    //         },
-    //     )
-    //     .with_style(Style {
-    //         position_type: PositionType::Absolute,
-    //         margin: UiRect::new(Val::Auto, Val::Auto, Val::Vh(20.0), Val::Auto),
-    //         display: Display::None,
-    //         ..default()
-    //     }),
-    // ));
+    // music, its volume is set from the settings and ducked while paused
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load("music.ogg"),
+            settings: PlaybackSettings::LOOP,
+        },
+        Music,
+    ));
 }
 
 // fn interact_play_button(
@@ -0,0 +1,487 @@
+use bevy::{prelude::*, transform::TransformSystem, utils::HashMap};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::utils::AssetHandle;
+
+/// Quads shared by all emitters; particles beyond this aren't drawn.
+static MAX_PARTICLE_QUADS: usize = 512;
+/// Steps per color channel of the shared particle materials.
+static PARTICLE_COLOR_STEPS: f32 = 15.0;
+
+/// CPU simulated particles, drawn as unlit quads facing the camera.
+///
+/// All emitters draw with one pool of quad entities, which grows up to
+/// `MAX_PARTICLE_QUADS` and is reused as particles die, so bursts don't spawn or despawn
+/// entities per particle. Colors are rounded to a small palette of shared materials.
+/// The simulation only depends on the emitter's seed and the deltas it is stepped with.
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticleQuads>()
+            .init_resource::<ParticleMaterials>()
+            .add_systems(Startup, load_particle_mesh)
+            .add_systems(Update, step_particle_emitters)
+            .add_systems(
+                PostUpdate,
+                draw_particles.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ParticleConfig {
+    /// Upper bound of live particles, and the size of the entity pool.
+    pub max_particles: usize,
+    /// Seconds, picked per particle in this range.
+    pub lifetime: (f32, f32),
+    /// World units per second, picked per particle in this range.
+    pub speed: (f32, f32),
+    /// Center of the emission fan, in the emitter's space.
+    pub direction: Vec3,
+    /// Half angle of the emission fan around the view axis, in radians.
+    pub spread: f32,
+    pub gravity: Vec3,
+    /// Velocity lost per second, as a fraction.
+    pub drag: f32,
+    /// Radians per second, picked per particle in -spin..=spin.
+    pub spin: f32,
+    /// Start color, picked per particle.
+    pub colors: Vec<Color>,
+    /// Color at the end of life; `None` keeps the start color.
+    pub end_color: Option<Color>,
+    pub start_size: f32,
+    pub end_size: f32,
+}
+
+impl Default for ParticleConfig {
+    fn default() -> Self {
+        Self {
+            max_particles: 64,
+            lifetime: (0.5, 1.0),
+            speed: (1.0, 2.0),
+            direction: Vec3::Y,
+            spread: 0.5,
+            gravity: Vec3::ZERO,
+            drag: 0.0,
+            spin: 0.0,
+            colors: vec![Color::WHITE],
+            end_color: None,
+            start_size: 0.2,
+            end_size: 0.2,
+        }
+    }
+}
+
+impl ParticleConfig {
+    /// Tumbling paper shooting upwards and falling down.
+    pub fn confetti(colors: Vec<Color>) -> Self {
+        Self {
+            max_particles: 80,
+            lifetime: (1.2, 2.0),
+            speed: (5.0, 9.0),
+            spread: 0.6,
+            gravity: Vec3::new(0.0, -9.0, 0.0),
+            drag: 1.5,
+            spin: 8.0,
+            colors,
+            start_size: 0.2,
+            end_size: 0.15,
+            ..default()
+        }
+    }
+
+    /// A quick radial pop that fades out, e.g. when bumping into something.
+    pub fn sparks(color: Color) -> Self {
+        Self {
+            max_particles: 24,
+            lifetime: (0.3, 0.5),
+            speed: (3.0, 5.0),
+            spread: std::f32::consts::PI,
+            drag: 4.0,
+            colors: vec![color],
+            end_color: Some(color.with_a(0.0)),
+            start_size: 0.15,
+            end_size: 0.0,
+            ..default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EmitterMode {
+    /// Emits this many particles on the first step.
+    Burst(usize),
+    /// Particles per second until the emitter is removed.
+    Continuous(f32),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Around the view axis, in radians.
+    pub rotation: f32,
+    pub spin: f32,
+    pub age: f32,
+    pub lifetime: f32,
+    pub color: Color,
+}
+
+impl Particle {
+    /// 0 when emitted, 1 when it dies.
+    pub fn progress(&self) -> f32 {
+        (self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct ParticleEmitter {
+    pub config: ParticleConfig,
+    pub mode: EmitterMode,
+    /// Stops emitting but lets live particles finish.
+    pub paused: bool,
+    /// Despawns the emitter once a burst is over.
+    pub despawn_when_finished: bool,
+    particles: Vec<Particle>,
+    rng: StdRng,
+    emit_accumulator: f32,
+    burst_done: bool,
+}
+
+impl ParticleEmitter {
+    pub fn new(config: ParticleConfig, mode: EmitterMode, seed: u64) -> Self {
+        Self {
+            particles: Vec::with_capacity(config.max_particles),
+            config,
+            mode,
+            paused: false,
+            despawn_when_finished: true,
+            rng: StdRng::seed_from_u64(seed),
+            emit_accumulator: 0.0,
+            burst_done: false,
+        }
+    }
+
+    pub fn burst(config: ParticleConfig, count: usize, seed: u64) -> Self {
+        Self::new(config, EmitterMode::Burst(count), seed)
+    }
+
+    pub fn continuous(config: ParticleConfig, rate: f32, seed: u64) -> Self {
+        Self::new(config, EmitterMode::Continuous(rate), seed)
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// A burst that has emitted and whose particles have all died.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.mode, EmitterMode::Burst(_)) && self.burst_done && self.particles.is_empty()
+    }
+
+    /// Emits up to `count` particles, fewer if the pool is full.
+    pub fn emit(&mut self, count: usize) {
+        let free = self
+            .config
+            .max_particles
+            .saturating_sub(self.particles.len());
+        for _ in 0..count.min(free) {
+            let particle = self.new_particle();
+            self.particles.push(particle);
+        }
+    }
+
+    /// Emits new particles, then ages, moves and removes the old ones.
+    pub fn step(&mut self, delta: f32) {
+        if !self.paused {
+            match self.mode {
+                EmitterMode::Burst(count) => {
+                    if !self.burst_done {
+                        self.burst_done = true;
+                        self.emit(count);
+                    }
+                }
+                EmitterMode::Continuous(rate) => {
+                    self.emit_accumulator += rate * delta;
+                    let count = self.emit_accumulator.floor();
+                    self.emit_accumulator -= count;
+                    self.emit(count as usize);
+                }
+            }
+        }
+
+        let drag = (1.0 - self.config.drag * delta).max(0.0);
+        for particle in self.particles.iter_mut() {
+            particle.age += delta;
+            particle.velocity = (particle.velocity + self.config.gravity * delta) * drag;
+            particle.position += particle.velocity * delta;
+            particle.rotation += particle.spin * delta;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+    }
+
+    pub fn color_of(&self, particle: &Particle) -> Color {
+        let Some(end) = self.config.end_color else {
+            return particle.color;
+        };
+        let start: Vec4 = particle.color.into();
+        let end: Vec4 = end.into();
+        start.lerp(end, particle.progress()).into()
+    }
+
+    pub fn size_of(&self, particle: &Particle) -> f32 {
+        let t = particle.progress();
+        self.config.start_size + (self.config.end_size - self.config.start_size) * t
+    }
+
+    fn new_particle(&mut self) -> Particle {
+        let config = &self.config;
+        let rng = &mut self.rng;
+
+        // within `spread` of `direction`, fanned out on the view plane
+        let direction = config.direction.try_normalize().unwrap_or(Vec3::Y);
+        let angle = rng.gen_range(-1.0..=1.0) * config.spread;
+        let around = Quat::from_rotation_arc(Vec3::Y, direction);
+        let velocity = around * (Quat::from_rotation_z(angle) * Vec3::Y);
+
+        let speed = gen_between(rng, config.speed);
+        let color = if config.colors.is_empty() {
+            Color::WHITE
+        } else {
+            config.colors[rng.gen_range(0..config.colors.len())]
+        };
+
+        Particle {
+            position: Vec3::ZERO,
+            velocity: velocity * speed,
+            rotation: rng.gen_range(0.0..std::f32::consts::TAU),
+            spin: rng.gen_range(-1.0..=1.0) * config.spin,
+            age: 0.0,
+            lifetime: gen_between(rng, config.lifetime).max(f32::EPSILON),
+            color,
+        }
+    }
+}
+
+fn gen_between(rng: &mut StdRng, (min, max): (f32, f32)) -> f32 {
+    if max > min {
+        rng.gen_range(min..max)
+    } else {
+        min
+    }
+}
+
+#[derive(Bundle)]
+pub struct ParticleEmitterBundle {
+    pub emitter: ParticleEmitter,
+    pub spatial: SpatialBundle,
+}
+
+impl ParticleEmitterBundle {
+    pub fn new(emitter: ParticleEmitter, translation: Vec3) -> Self {
+        Self {
+            emitter,
+            spatial: SpatialBundle::from_transform(Transform::from_translation(translation)),
+        }
+    }
+}
+
+/// The quad entities particles are drawn with, in world space.
+#[derive(Resource, Default)]
+struct ParticleQuads(Vec<Entity>);
+
+/// Unlit materials by rounded color, shared by all particle quads.
+#[derive(Resource, Default)]
+struct ParticleMaterials(HashMap<[u8; 4], Handle<StandardMaterial>>);
+
+impl ParticleMaterials {
+    fn get_or_add(
+        &mut self,
+        color: Color,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> Handle<StandardMaterial> {
+        let key = color
+            .as_rgba_f32()
+            .map(|channel| (channel.clamp(0.0, 1.0) * PARTICLE_COLOR_STEPS).round() as u8);
+        self.0
+            .entry(key)
+            .or_insert_with(|| {
+                let [r, g, b, a] = key.map(|step| step as f32 / PARTICLE_COLOR_STEPS);
+                materials.add(StandardMaterial {
+                    base_color: Color::rgba(r, g, b, a),
+                    unlit: true,
+                    alpha_mode: AlphaMode::Blend,
+                    ..default()
+                })
+            })
+            .clone()
+    }
+}
+
+fn load_particle_mesh(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(AssetHandle::<ParticleEmitter, Mesh>::new(
+        meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE))),
+    ));
+}
+
+fn step_particle_emitters(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut q_emitter: Query<(Entity, &mut ParticleEmitter)>,
+) {
+    for (entity, mut emitter) in q_emitter.iter_mut() {
+        emitter.step(time.delta_seconds());
+        if emitter.is_finished() && emitter.despawn_when_finished {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn draw_particles(
+    mut commands: Commands,
+    particle_mesh: Res<AssetHandle<ParticleEmitter, Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut particle_materials: ResMut<ParticleMaterials>,
+    mut quads: ResMut<ParticleQuads>,
+    q_camera: Query<&GlobalTransform, With<Camera3d>>,
+    q_emitter: Query<(&ParticleEmitter, &GlobalTransform)>,
+    mut q_quad: Query<(
+        &mut Transform,
+        &mut Visibility,
+        &mut Handle<StandardMaterial>,
+    )>,
+) {
+    let camera_rotation = q_camera.get_single().map_or(Quat::IDENTITY, |camera| {
+        camera.to_scale_rotation_translation().1
+    });
+
+    let mut live = 0;
+    let mut free_quads = quads.0.iter();
+    for (emitter, global_transform) in q_emitter.iter() {
+        live += emitter.particles().len();
+        for particle in emitter.particles() {
+            let Some(quad) = free_quads.next() else {
+                break;
+            };
+            let Ok((mut transform, mut visibility, mut material)) = q_quad.get_mut(*quad) else {
+                continue;
+            };
+
+            // particles move in the emitter's space, and face the camera
+            *visibility = Visibility::Inherited;
+            *transform = Transform {
+                translation: global_transform.transform_point(particle.position),
+                rotation: camera_rotation * Quat::from_rotation_z(particle.rotation),
+                scale: Vec3::splat(emitter.size_of(particle)),
+            };
+            let shared = particle_materials.get_or_add(emitter.color_of(particle), &mut materials);
+            if *material != shared {
+                *material = shared;
+            }
+        }
+    }
+
+    for quad in free_quads {
+        if let Ok((_, mut visibility, _)) = q_quad.get_mut(*quad) {
+            if *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+
+    // the new quads are drawn from the next frame on
+    let missing = live.min(MAX_PARTICLE_QUADS).saturating_sub(quads.0.len());
+    for _ in 0..missing {
+        let quad = commands
+            .spawn(PbrBundle {
+                mesh: particle_mesh.handle.clone(),
+                visibility: Visibility::Hidden,
+                ..default()
+            })
+            .id();
+        quads.0.push(quad);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_particles: usize) -> ParticleConfig {
+        ParticleConfig {
+            max_particles,
+            lifetime: (0.5, 1.0),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn burst_emits_once_and_is_seeded() {
+        let mut emitter = ParticleEmitter::burst(config(64), 10, 7);
+        emitter.step(0.0);
+        assert_eq!(emitter.particles().len(), 10);
+        emitter.step(0.1);
+        assert_eq!(emitter.particles().len(), 10);
+
+        let mut same_seed = ParticleEmitter::burst(config(64), 10, 7);
+        same_seed.step(0.0);
+        same_seed.step(0.1);
+        assert_eq!(emitter.particles(), same_seed.particles());
+
+        let mut other_seed = ParticleEmitter::burst(config(64), 10, 8);
+        other_seed.step(0.0);
+        other_seed.step(0.1);
+        assert_ne!(emitter.particles(), other_seed.particles());
+    }
+
+    #[test]
+    fn particles_expire_after_their_lifetime() {
+        let mut emitter = ParticleEmitter::burst(config(64), 20, 1);
+        emitter.step(0.0);
+        assert!(!emitter.is_finished());
+        for particle in emitter.particles() {
+            assert!((0.5..1.0).contains(&particle.lifetime));
+        }
+
+        emitter.step(0.75);
+        assert!(emitter.particles().len() < 20);
+        assert!(emitter
+            .particles()
+            .iter()
+            .all(|particle| particle.age < particle.lifetime));
+
+        emitter.step(0.25);
+        assert!(emitter.particles().is_empty());
+        assert!(emitter.is_finished());
+    }
+
+    #[test]
+    fn pool_caps_live_particles() {
+        let mut burst = ParticleEmitter::burst(config(24), 100, 3);
+        burst.step(0.0);
+        assert_eq!(burst.particles().len(), 24);
+
+        let mut continuous = ParticleEmitter::continuous(config(24), 1000.0, 3);
+        for _ in 0..10 {
+            continuous.step(0.01);
+            assert!(continuous.particles().len() <= 24);
+        }
+        assert_eq!(continuous.particles().len(), 24);
+        assert!(!continuous.is_finished());
+    }
+
+    #[test]
+    fn continuous_emits_at_its_rate_until_paused() {
+        let mut emitter = ParticleEmitter::continuous(config(64), 10.0, 5);
+        emitter.step(0.25);
+        assert_eq!(emitter.particles().len(), 2);
+        emitter.step(0.25);
+        assert_eq!(emitter.particles().len(), 5);
+
+        emitter.paused = true;
+        emitter.step(0.25);
+        assert!(emitter.particles().len() <= 5);
+    }
+}
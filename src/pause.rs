@@ -0,0 +1,276 @@
+use bevy::{prelude::*, window::WindowFocused};
+
+use crate::{
+    button::ButtonCommands,
+    camera::SafeAreaPadding,
+    focus::{FocusActivated, FocusBack, Focusable, Focused, InputBlocked},
+    settings::{OpenSettings, Settings, SettingsState},
+    theme::{ThemedBackground, UiTheme},
+    transition::TransitionTo,
+    widgets::{Modal, ModalClosed, WidgetCommands},
+    GameState,
+};
+
+/// Music volume while paused, relative to the music volume setting.
+pub static PAUSED_MUSIC_VOLUME_FACTOR: f32 = 0.3;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<PauseState>()
+            .add_systems(
+                Update,
+                (toggle_pause, pause_on_focus_loss).run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (interact_pause_menu, update_mute_icon).run_if(in_state(PauseState::Paused)),
+            )
+            .add_systems(OnEnter(PauseState::Paused), (freeze_time, spawn_pause_menu))
+            .add_systems(
+                OnExit(PauseState::Paused),
+                (unfreeze_time, despawn_pause_menu),
+            )
+            .add_systems(OnExit(GameState::Playing), resume_on_exit_playing);
+
+        #[cfg(target_arch = "wasm32")]
+        app.add_systems(Startup, crate::web_event::listen_visibility_change)
+            .add_systems(
+                Update,
+                pause_on_document_hidden.run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Sub-state of `GameState::Playing`. Always `Running` outside of `Playing`.
+#[derive(States, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+#[derive(Component)]
+struct PauseMenu;
+
+#[derive(Component, Clone, Copy)]
+enum PauseMenuButton {
+    Resume,
+    Settings,
+    QuitToMenu,
+    Mute,
+}
+
+/// Index of the quit button of the quit confirmation dialog.
+static QUIT_CONFIRM_QUIT: usize = 1;
+
+fn toggle_pause(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut back: EventReader<FocusBack>,
+    settings_state: Res<State<SettingsState>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    input_blocked: Res<InputBlocked>,
+    q_modal: Query<(), With<Modal>>,
+) {
+    let start_pressed = gamepads.iter().any(|gamepad| {
+        gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::Start))
+    });
+    let back_pressed = back.iter().next().is_some();
+
+    // the settings screen handles back itself, and the quit dialog has to be answered
+    if settings_state.get() == &SettingsState::Open || !q_modal.is_empty() || input_blocked.0 {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) || start_pressed || back_pressed {
+        next_pause_state.set(match pause_state.get() {
+            PauseState::Running => PauseState::Paused,
+            PauseState::Paused => PauseState::Running,
+        });
+    }
+}
+
+fn pause_on_focus_loss(
+    mut focus_reader: EventReader<WindowFocused>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if focus_reader.iter().any(|event| !event.focused) {
+        next_pause_state.set(PauseState::Paused);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn pause_on_document_hidden(mut next_pause_state: ResMut<NextState<PauseState>>) {
+    if crate::web_event::take_document_was_hidden() {
+        next_pause_state.set(PauseState::Paused);
+    }
+}
+
+fn resume_on_exit_playing(mut next_pause_state: ResMut<NextState<PauseState>>) {
+    next_pause_state.set(PauseState::Running);
+}
+
+// animation players and tweens advance by `Time::delta`, so this freezes them too,
+// while screen transitions and button cooldowns keep running on the raw delta
+fn freeze_time(mut time: ResMut<Time>) {
+    time.pause();
+}
+
+fn unfreeze_time(mut time: ResMut<Time>) {
+    time.unpause();
+}
+
+fn spawn_pause_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    theme: Res<UiTheme>,
+    mut focused: ResMut<Focused>,
+) {
+    let mut buttons = [
+        ("Resume", PauseMenuButton::Resume),
+        ("Settings", PauseMenuButton::Settings),
+        ("Menu", PauseMenuButton::QuitToMenu),
+    ]
+    .iter()
+    .enumerate()
+    .map(|(order, (text, button))| {
+        commands
+            .text_button(&theme, text)
+            .spawn()
+            .insert((
+                *button,
+                Focusable {
+                    order: order as i32,
+                },
+            ))
+            .id()
+    })
+    .collect::<Vec<_>>();
+
+    let mute_button = commands
+        .icon_button(&theme, asset_server.load("volume.png"))
+        .icon_size(32.0)
+        .spawn()
+        .insert((
+            PauseMenuButton::Mute,
+            Focusable {
+                order: buttons.len() as i32,
+            },
+        ))
+        .id();
+    buttons.push(mute_button);
+
+    // focus resume so a gamepad can unpause right away
+    focused.0 = buttons.first().copied();
+
+    commands
+        .spawn((
+            PauseMenu,
+            SafeAreaPadding,
+            ThemedBackground::Overlay,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: theme.overlay.into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+        ))
+        .push_children(&buttons);
+}
+
+fn despawn_pause_menu(mut commands: Commands, q_pause_menu: Query<Entity, With<PauseMenu>>) {
+    for entity in q_pause_menu.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn interact_pause_menu(
+    mut commands: Commands,
+    mut activated: EventReader<FocusActivated>,
+    mut modal_closed: EventReader<ModalClosed>,
+    q_button: Query<(Entity, &PauseMenuButton)>,
+    q_pause_menu: Query<Entity, With<PauseMenu>>,
+    mut quit_confirm: Local<Option<Entity>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut transition: EventWriter<TransitionTo>,
+    mut open_settings: EventWriter<OpenSettings>,
+    mut settings: ResMut<Settings>,
+    mut focused: ResMut<Focused>,
+    theme: Res<UiTheme>,
+) {
+    for event in activated.iter() {
+        match q_button.get(event.0).map(|(_, button)| button) {
+            Ok(PauseMenuButton::Resume) => next_pause_state.set(PauseState::Running),
+            Ok(PauseMenuButton::Settings) => open_settings.send(OpenSettings),
+            Ok(PauseMenuButton::QuitToMenu) => {
+                let modal = commands
+                    .modal(&theme, "Quit to menu?")
+                    .message("The current run will be lost.")
+                    .button("Cancel")
+                    .button("Quit")
+                    .spawn()
+                    .id();
+                // despawned with the pause menu
+                for pause_menu in q_pause_menu.iter() {
+                    commands.entity(pause_menu).add_child(modal);
+                }
+                *quit_confirm = Some(modal);
+            }
+            Ok(PauseMenuButton::Mute) => settings.muted = !settings.muted,
+            Err(_) => {}
+        }
+    }
+
+    for ModalClosed { modal, button } in modal_closed.iter() {
+        if *quit_confirm != Some(*modal) {
+            continue;
+        }
+        *quit_confirm = None;
+
+        if *button == QUIT_CONFIRM_QUIT {
+            transition.send(TransitionTo(GameState::Menu));
+        } else {
+            focused.0 = q_button
+                .iter()
+                .find(|(_, button)| matches!(button, PauseMenuButton::QuitToMenu))
+                .map(|(entity, _)| entity);
+        }
+    }
+}
+
+/// Dims the icon of the mute button while muted.
+fn update_mute_icon(
+    settings: Res<Settings>,
+    theme: Res<UiTheme>,
+    q_button: Query<(Ref<PauseMenuButton>, &Children)>,
+    mut q_icon: Query<&mut BackgroundColor, With<UiImage>>,
+) {
+    for (button, children) in q_button.iter() {
+        if !matches!(*button, PauseMenuButton::Mute)
+            || !(button.is_added() || settings.is_changed())
+        {
+            continue;
+        }
+        let color = if settings.muted {
+            theme.text.with_a(0.4)
+        } else {
+            theme.text
+        };
+        let mut icons = q_icon.iter_many_mut(children);
+        while let Some(mut icon) = icons.fetch_next() {
+            icon.0 = color;
+        }
+    }
+}
@@ -0,0 +1,426 @@
+use bevy::{
+    core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping},
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        texture::BevyDefault,
+        view::ColorGrading,
+    },
+    ui::FocusPolicy,
+};
+use bevy_tweening::{component_animator_system, AnimationSystem, Animator, Lens};
+
+use crate::{
+    settings::{Quality, Settings},
+    transition::TransitionSettings,
+    warmup::ShaderWarmup,
+    GameState,
+};
+
+/// Bloom, tonemapping, color grading and a vignette for cameras with `PostProcess`.
+///
+/// Each `GameState` has a preset in `PostProcessPresets`, which the camera eases into
+/// while a screen transition reveals the new state. What is actually applied is limited
+/// by the quality setting, see `PostProcessLevel`. An offscreen camera draws the shader
+/// warmup with the HDR mode the quality setting doesn't use, so changing it doesn't hitch.
+pub struct PostProcessPlugin;
+
+impl Plugin for PostProcessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PostProcessPresets>()
+            .add_systems(Startup, spawn_vignette)
+            .add_systems(OnEnter(GameState::Loading), spawn_warmup_camera)
+            .add_systems(
+                Update,
+                component_animator_system::<PostProcess>.in_set(AnimationSystem::AnimationUpdate),
+            )
+            .add_systems(
+                Update,
+                (
+                    tween_post_process_to_state,
+                    apply_post_process.after(AnimationSystem::AnimationUpdate),
+                ),
+            );
+    }
+}
+
+/// Bevy has no custom color grading LUTs yet, so the grading is limited to `exposure` and
+/// `saturation`. The LUTs from the `tonemapping_luts` feature are picked by `tonemapping`,
+/// e.g. `TonyMcMapface`, `AgX` or `BlenderFilmic`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PostProcessPreset {
+    pub bloom: f32,
+    pub tonemapping: Tonemapping,
+    /// Opacity of the darkened corners, 0 disables it.
+    pub vignette: f32,
+    /// In stops.
+    pub exposure: f32,
+    pub saturation: f32,
+}
+
+impl Default for PostProcessPreset {
+    fn default() -> Self {
+        Self {
+            bloom: 0.0,
+            tonemapping: Tonemapping::TonyMcMapface,
+            vignette: 0.0,
+            exposure: 0.0,
+            saturation: 1.0,
+        }
+    }
+}
+
+impl PostProcessPreset {
+    /// Tonemapping can't be blended, it switches to `end` right away.
+    pub fn lerp(&self, end: &Self, ratio: f32) -> Self {
+        let lerp = |start: f32, end: f32| start + (end - start) * ratio;
+        Self {
+            bloom: lerp(self.bloom, end.bloom),
+            tonemapping: end.tonemapping,
+            vignette: lerp(self.vignette, end.vignette),
+            exposure: lerp(self.exposure, end.exposure),
+            saturation: lerp(self.saturation, end.saturation),
+        }
+    }
+
+    /// The parts of the preset that `level` allows.
+    pub fn limited_to(&self, level: PostProcessLevel) -> Self {
+        match level {
+            PostProcessLevel::Off => Self::default(),
+            PostProcessLevel::Basic => Self {
+                bloom: 0.0,
+                ..*self
+            },
+            PostProcessLevel::Full => *self,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PostProcessLevel {
+    /// Default tonemapping only.
+    Off,
+    /// No HDR, so no bloom.
+    Basic,
+    Full,
+}
+
+impl PostProcessLevel {
+    pub fn for_quality(quality: Quality) -> Self {
+        // bloom needs an HDR target, too slow on WebGL2
+        if cfg!(target_arch = "wasm32") {
+            return match quality {
+                Quality::Low => PostProcessLevel::Off,
+                Quality::Medium | Quality::High => PostProcessLevel::Basic,
+            };
+        }
+        match quality {
+            Quality::Low => PostProcessLevel::Off,
+            Quality::Medium => PostProcessLevel::Basic,
+            Quality::High => PostProcessLevel::Full,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy)]
+pub struct PostProcessPresets {
+    pub loading: PostProcessPreset,
+    pub menu: PostProcessPreset,
+    pub playing: PostProcessPreset,
+}
+
+impl Default for PostProcessPresets {
+    fn default() -> Self {
+        Self {
+            loading: PostProcessPreset::default(),
+            menu: PostProcessPreset {
+                bloom: 0.2,
+                vignette: 0.35,
+                saturation: 1.1,
+                ..default()
+            },
+            playing: PostProcessPreset {
+                bloom: 0.1,
+                vignette: 0.2,
+                ..default()
+            },
+        }
+    }
+}
+
+impl PostProcessPresets {
+    pub fn for_state(&self, state: &GameState) -> PostProcessPreset {
+        match state {
+            GameState::Loading => self.loading,
+            GameState::Menu => self.menu,
+            GameState::Playing => self.playing,
+        }
+    }
+}
+
+/// The preset currently applied to this camera, before the quality limit.
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct PostProcess(pub PostProcessPreset);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PostProcessLens {
+    pub start: PostProcessPreset,
+    pub end: PostProcessPreset,
+}
+
+impl Lens<PostProcess> for PostProcessLens {
+    fn lerp(&mut self, target: &mut PostProcess, ratio: f32) {
+        target.0 = self.start.lerp(&self.end, ratio);
+    }
+}
+
+#[derive(Component)]
+struct Vignette;
+
+fn vignette_image(size: u32) -> Image {
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    let center = (size - 1) as f32 / 2.0;
+    for y in 0..size {
+        for x in 0..size {
+            let offset = Vec2::new(x as f32 - center, y as f32 - center) / center;
+            // clear in the middle, darkest in the corners
+            let t = ((offset.length() - 0.5) / (std::f32::consts::SQRT_2 - 0.5)).clamp(0.0, 1.0);
+            let alpha = t * t * (3.0 - 2.0 * t);
+            data.extend_from_slice(&[0, 0, 0, (alpha * 255.0) as u8]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn spawn_vignette(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.spawn((
+        Vignette,
+        ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            image: images.add(vignette_image(64)).into(),
+            background_color: Color::WHITE.with_a(0.0).into(),
+            focus_policy: FocusPolicy::Pass,
+            // over the 3D scene, under every other UI
+            z_index: ZIndex::Global(-1),
+            ..default()
+        },
+    ));
+}
+
+// `apply_post_process` turns HDR on and off with the quality setting, and each needs its
+// own pipelines, so an offscreen camera draws the warmup with the other one
+fn spawn_warmup_camera(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    settings: Res<Settings>,
+) {
+    let hdr = PostProcessLevel::for_quality(settings.quality) != PostProcessLevel::Full;
+    // never used on the web
+    if hdr && cfg!(target_arch = "wasm32") {
+        return;
+    }
+
+    // big enough for every bloom mip
+    let size = Extent3d {
+        width: 256,
+        height: 256,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::bevy_default(),
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+
+    let mut camera = commands.spawn((
+        ShaderWarmup,
+        Camera3dBundle {
+            camera: Camera {
+                hdr,
+                order: -1,
+                target: RenderTarget::Image(images.add(image)),
+                ..default()
+            },
+            projection: OrthographicProjection::default().into(),
+            ..default()
+        },
+    ));
+    if hdr {
+        camera.insert(BloomSettings::default());
+    }
+}
+
+fn tween_post_process_to_state(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    presets: Res<PostProcessPresets>,
+    transition_settings: Res<TransitionSettings>,
+    q_camera: Query<(Entity, &PostProcess)>,
+) {
+    if !state.is_changed() && !presets.is_changed() {
+        return;
+    }
+
+    let target = presets.for_state(state.get());
+    for (entity, post_process) in q_camera.iter() {
+        commands
+            .entity(entity)
+            .insert(Animator::new(transition_settings.reveal_tween(
+                PostProcessLens {
+                    start: post_process.0,
+                    end: target,
+                },
+            )));
+    }
+}
+
+fn apply_post_process(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut q_camera: Query<(
+        Entity,
+        Ref<PostProcess>,
+        &mut Camera,
+        &mut Tonemapping,
+        &mut ColorGrading,
+        Option<&mut BloomSettings>,
+    )>,
+    mut q_vignette: Query<&mut BackgroundColor, With<Vignette>>,
+) {
+    let level = PostProcessLevel::for_quality(settings.quality);
+
+    for (entity, post_process, mut camera, mut tonemapping, mut color_grading, bloom) in
+        q_camera.iter_mut()
+    {
+        if !post_process.is_changed() && !settings.is_changed() {
+            continue;
+        }
+        let preset = post_process.0.limited_to(level);
+
+        let hdr = level == PostProcessLevel::Full;
+        if camera.hdr != hdr {
+            camera.hdr = hdr;
+        }
+        if *tonemapping != preset.tonemapping {
+            *tonemapping = preset.tonemapping;
+        }
+        color_grading.exposure = preset.exposure;
+        color_grading.post_saturation = preset.saturation;
+
+        match bloom {
+            Some(mut bloom) if preset.bloom > 0.0 => bloom.intensity = preset.bloom,
+            Some(_) => {
+                commands.entity(entity).remove::<BloomSettings>();
+            }
+            None if preset.bloom > 0.0 => {
+                commands.entity(entity).insert(BloomSettings {
+                    intensity: preset.bloom,
+                    ..default()
+                });
+            }
+            None => {}
+        }
+
+        for mut vignette in q_vignette.iter_mut() {
+            vignette.0.set_a(preset.vignette);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset() -> PostProcessPreset {
+        PostProcessPreset {
+            bloom: 0.4,
+            tonemapping: Tonemapping::AgX,
+            vignette: 0.5,
+            exposure: -1.0,
+            saturation: 1.5,
+        }
+    }
+
+    #[test]
+    fn lerp_blends_everything_but_tonemapping() {
+        let start = PostProcessPreset::default();
+        let end = preset();
+
+        assert_eq!(start.lerp(&end, 1.0), end);
+        let halfway = start.lerp(&end, 0.5);
+        assert_eq!(
+            halfway,
+            PostProcessPreset {
+                bloom: 0.2,
+                tonemapping: Tonemapping::AgX,
+                vignette: 0.25,
+                exposure: -0.5,
+                saturation: 1.25,
+            }
+        );
+        assert_eq!(start.lerp(&end, 0.0).tonemapping, Tonemapping::AgX);
+    }
+
+    #[test]
+    fn lower_levels_drop_effects() {
+        let preset = preset();
+        assert_eq!(preset.limited_to(PostProcessLevel::Full), preset);
+        assert_eq!(
+            preset.limited_to(PostProcessLevel::Basic),
+            PostProcessPreset {
+                bloom: 0.0,
+                ..preset
+            }
+        );
+        assert_eq!(
+            preset.limited_to(PostProcessLevel::Off),
+            PostProcessPreset::default()
+        );
+    }
+
+    #[test]
+    fn quality_picks_the_level() {
+        assert_eq!(
+            PostProcessLevel::for_quality(Quality::Low),
+            PostProcessLevel::Off
+        );
+        assert_eq!(
+            PostProcessLevel::for_quality(Quality::Medium),
+            PostProcessLevel::Basic
+        );
+        let high = PostProcessLevel::for_quality(Quality::High);
+        if cfg!(target_arch = "wasm32") {
+            assert_eq!(high, PostProcessLevel::Basic);
+        } else {
+            assert_eq!(high, PostProcessLevel::Full);
+        }
+    }
+}
@@ -0,0 +1,363 @@
+use bevy::{
+    pbr::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    scene::SceneInstance,
+};
+
+/// Sends `SceneInstanceReady` once the children of a `SceneBundle` have been spawned.
+///
+/// Post-processing of a spawned scene goes in `SceneReadySet::PostProcess`, reading
+/// `SceneInstanceReady`, like `ToonShading`. `SceneShadows` and `SceneMaterialOverride`
+/// on the root, and `AttachToBone` on its children, are handled here. Material overrides
+/// are applied before `SceneReadySet::PostProcess`, so it starts from them.
+pub struct SceneReadyPlugin;
+
+impl Plugin for SceneReadyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SceneInstanceReady>()
+            .configure_sets(
+                Update,
+                (SceneReadySet::Detect, SceneReadySet::PostProcess).chain(),
+            )
+            .add_systems(
+                Update,
+                (detect_ready_scenes, apply_scene_material_override)
+                    .chain()
+                    .in_set(SceneReadySet::Detect),
+            )
+            .add_systems(
+                Update,
+                (apply_scene_shadows, attach_to_bones).in_set(SceneReadySet::PostProcess),
+            );
+    }
+}
+
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum SceneReadySet {
+    Detect,
+    PostProcess,
+}
+
+#[derive(Event)]
+pub struct SceneInstanceReady {
+    pub root: Entity,
+}
+
+/// Marks scene roots that already sent `SceneInstanceReady`.
+#[derive(Component)]
+pub struct SceneReady;
+
+/// Shadow settings for every mesh in the scene.
+#[derive(Component, Clone, Copy)]
+pub struct SceneShadows {
+    pub cast: bool,
+    pub receive: bool,
+}
+
+/// Replaces the material of every mesh in the scene.
+#[derive(Component, Clone)]
+pub struct SceneMaterialOverride(pub Handle<StandardMaterial>);
+
+/// Spawn a prop as a child of a character's scene root with this, and it is moved under
+/// the named bone once the scene is ready. `offset` is relative to the bone.
+#[derive(Component, Clone)]
+pub struct AttachToBone {
+    pub bone_name: String,
+    pub offset: Transform,
+}
+
+/// Replaces `AttachToBone` once the prop has been moved under the bone.
+#[derive(Component)]
+pub struct AttachedToBone(pub Entity);
+
+/// The descendant of `root` with the given `Name`, e.g. a bone like `mixamorig:Head`.
+pub fn find_named_descendant(
+    root: Entity,
+    name: &str,
+    q_children: &Query<&Children>,
+    q_name: &Query<&Name>,
+) -> Option<Entity> {
+    q_children
+        .iter_descendants(root)
+        .find(|entity| q_name.get(*entity).map_or(false, |n| n.as_str() == name))
+}
+
+fn detect_ready_scenes(
+    mut commands: Commands,
+    scene_spawner: Res<SceneSpawner>,
+    q_root: Query<(Entity, &SceneInstance), Without<SceneReady>>,
+    mut ready: EventWriter<SceneInstanceReady>,
+) {
+    for (root, scene_instance) in q_root.iter() {
+        if scene_spawner.instance_is_ready(**scene_instance) {
+            commands.entity(root).insert(SceneReady);
+            ready.send(SceneInstanceReady { root });
+        }
+    }
+}
+
+fn apply_scene_shadows(
+    mut commands: Commands,
+    mut ready: EventReader<SceneInstanceReady>,
+    q_shadows: Query<&SceneShadows>,
+    q_children: Query<&Children>,
+    q_mesh: Query<(), With<Handle<Mesh>>>,
+) {
+    for SceneInstanceReady { root } in ready.iter() {
+        let Ok(shadows) = q_shadows.get(*root) else {
+            continue;
+        };
+
+        for entity in q_children.iter_descendants(*root) {
+            if !q_mesh.contains(entity) {
+                continue;
+            }
+            let mut e = commands.entity(entity);
+            if shadows.cast {
+                e.remove::<NotShadowCaster>();
+            } else {
+                e.insert(NotShadowCaster);
+            }
+            if shadows.receive {
+                e.remove::<NotShadowReceiver>();
+            } else {
+                e.insert(NotShadowReceiver);
+            }
+        }
+    }
+}
+
+fn apply_scene_material_override(
+    mut ready: EventReader<SceneInstanceReady>,
+    q_override: Query<&SceneMaterialOverride>,
+    q_children: Query<&Children>,
+    mut q_material: Query<&mut Handle<StandardMaterial>>,
+) {
+    for SceneInstanceReady { root } in ready.iter() {
+        let Ok(material_override) = q_override.get(*root) else {
+            continue;
+        };
+
+        for entity in q_children.iter_descendants(*root) {
+            if let Ok(mut material) = q_material.get_mut(entity) {
+                *material = material_override.0.clone();
+            }
+        }
+    }
+}
+
+fn attach_to_bones(
+    mut commands: Commands,
+    q_prop: Query<(Entity, &AttachToBone, &Parent)>,
+    q_ready: Query<(), With<SceneReady>>,
+    q_children: Query<&Children>,
+    q_name: Query<&Name>,
+) {
+    for (prop, attach, parent) in q_prop.iter() {
+        let root = parent.get();
+        if !q_ready.contains(root) {
+            continue;
+        }
+
+        let mut e = commands.entity(prop);
+        e.remove::<AttachToBone>();
+        let Some(bone) = find_named_descendant(root, &attach.bone_name, &q_children, &q_name)
+        else {
+            warn!(
+                "can't attach {:?}: no bone named {:?} in the scene of {:?}",
+                prop, attach.bone_name, root
+            );
+            continue;
+        };
+        e.insert((AttachedToBone(bone), attach.offset))
+            .set_parent(bone);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{
+        asset::{AssetPlugin, HandleId},
+        ecs::event::ManualEventReader,
+        scene::ScenePlugin,
+    };
+
+    #[test]
+    fn ready_is_sent_once_after_the_scene_is_spawned() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), ScenePlugin))
+            .add_event::<SceneInstanceReady>()
+            .add_systems(Update, detect_ready_scenes);
+
+        let mut scene_world = World::new();
+        scene_world.spawn_empty();
+        let scene = app
+            .world
+            .resource_mut::<Assets<Scene>>()
+            .add(Scene::new(scene_world));
+        let root = app.world.spawn(scene).id();
+
+        let mut reader = ManualEventReader::<SceneInstanceReady>::default();
+        let mut ready = vec![];
+        for _ in 0..5 {
+            app.update();
+            let events = app.world.resource::<Events<SceneInstanceReady>>();
+            ready.extend(reader.iter(events).map(|event| event.root));
+        }
+
+        assert_eq!(ready, vec![root]);
+        assert!(app.world.get::<SceneReady>(root).is_some());
+        assert_eq!(app.world.get::<Children>(root).map(|c| c.len()), Some(1));
+    }
+
+    fn ready_scene_app() -> App {
+        let mut app = App::new();
+        app.add_event::<SceneInstanceReady>().add_systems(
+            Update,
+            (apply_scene_material_override, apply_scene_shadows).chain(),
+        );
+        app
+    }
+
+    #[test]
+    fn shadows_and_material_are_applied_to_every_mesh() {
+        let mut app = ready_scene_app();
+        let material = Handle::<StandardMaterial>::default();
+        let root = app
+            .world
+            .spawn((
+                SceneShadows {
+                    cast: false,
+                    receive: true,
+                },
+                SceneMaterialOverride(material.clone()),
+            ))
+            .id();
+        let mut meshes = vec![];
+        app.world.entity_mut(root).with_children(|root| {
+            let mut mesh = root.spawn((
+                Handle::<Mesh>::default(),
+                Handle::<StandardMaterial>::weak(HandleId::random::<StandardMaterial>()),
+                NotShadowReceiver,
+            ));
+            mesh.with_children(|mesh| {
+                meshes.push(mesh.spawn(Handle::<Mesh>::default()).id());
+            });
+            meshes.push(mesh.id());
+        });
+        let other = app
+            .world
+            .spawn(SceneShadows {
+                cast: false,
+                receive: false,
+            })
+            .with_children(|root| {
+                root.spawn(Handle::<Mesh>::default());
+            })
+            .id();
+
+        app.world.send_event(SceneInstanceReady { root });
+        app.update();
+
+        for mesh in meshes.iter() {
+            let mesh = app.world.entity(*mesh);
+            assert!(mesh.contains::<NotShadowCaster>());
+            assert!(!mesh.contains::<NotShadowReceiver>());
+        }
+        assert_eq!(
+            app.world.get::<Handle<StandardMaterial>>(meshes[1]),
+            Some(&material)
+        );
+        // not ready yet
+        let other_mesh = app.world.get::<Children>(other).unwrap()[0];
+        assert!(!app.world.entity(other_mesh).contains::<NotShadowCaster>());
+    }
+
+    /// A ready character scene: root > Armature > Hips > Head.
+    fn spawn_character(app: &mut App) -> (Entity, Entity) {
+        let root = app.world.spawn(SceneReady).id();
+        let mut head = Entity::PLACEHOLDER;
+        app.world.entity_mut(root).with_children(|root| {
+            root.spawn(Name::new("Armature")).with_children(|armature| {
+                armature
+                    .spawn(Name::new("mixamorig:Hips"))
+                    .with_children(|hips| {
+                        head = hips.spawn(Name::new("mixamorig:Head")).id();
+                    });
+            });
+        });
+        (root, head)
+    }
+
+    fn spawn_prop(app: &mut App, root: Entity, bone_name: &str) -> Entity {
+        let prop = app
+            .world
+            .spawn(AttachToBone {
+                bone_name: bone_name.to_owned(),
+                offset: Transform::from_xyz(0.0, 0.2, 0.0),
+            })
+            .id();
+        app.world.entity_mut(root).add_child(prop);
+        prop
+    }
+
+    fn attach_app() -> App {
+        let mut app = App::new();
+        app.add_systems(Update, attach_to_bones);
+        app
+    }
+
+    #[test]
+    fn prop_is_moved_under_bone() {
+        let mut app = attach_app();
+        let (root, head) = spawn_character(&mut app);
+        let prop = spawn_prop(&mut app, root, "mixamorig:Head");
+
+        app.update();
+
+        let prop = app.world.entity(prop);
+        assert_eq!(prop.get::<Parent>().map(Parent::get), Some(head));
+        assert_eq!(prop.get::<AttachedToBone>().map(|a| a.0), Some(head));
+        assert_eq!(
+            prop.get::<Transform>(),
+            Some(&Transform::from_xyz(0.0, 0.2, 0.0))
+        );
+        assert!(prop.get::<AttachToBone>().is_none());
+        assert!(app
+            .world
+            .get::<Children>(head)
+            .unwrap()
+            .contains(&prop.id()));
+    }
+
+    #[test]
+    fn missing_bone_leaves_prop_in_place() {
+        let mut app = attach_app();
+        let (root, _) = spawn_character(&mut app);
+        let prop = spawn_prop(&mut app, root, "mixamorig:Tail");
+
+        app.update();
+
+        // warned about once, and not retried
+        let prop = app.world.entity(prop);
+        assert_eq!(prop.get::<Parent>().map(Parent::get), Some(root));
+        assert!(prop.get::<AttachToBone>().is_none());
+        assert!(prop.get::<AttachedToBone>().is_none());
+    }
+
+    #[test]
+    fn waits_for_scene_to_be_ready() {
+        let mut app = attach_app();
+        let (root, head) = spawn_character(&mut app);
+        app.world.entity_mut(root).remove::<SceneReady>();
+        let prop = spawn_prop(&mut app, root, "mixamorig:Head");
+
+        app.update();
+        assert!(app.world.get::<AttachToBone>(prop).is_some());
+
+        app.world.entity_mut(root).insert(SceneReady);
+        app.update();
+        assert_eq!(app.world.get::<Parent>(prop).map(Parent::get), Some(head));
+    }
+}
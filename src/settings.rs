@@ -0,0 +1,543 @@
+use bevy::{
+    audio::AudioSinkPlayback,
+    prelude::*,
+    ui::FocusPolicy,
+    window::{PresentMode, PrimaryWindow, WindowMode},
+};
+use bevy_tweening::{Animator, EaseFunction, Tween};
+use std::time::Duration;
+
+use crate::{
+    button::{ButtonCommands, ButtonCooldown, ButtonSize},
+    camera::SafeAreaPadding,
+    focus::{FocusActivated, FocusBack, FocusTrap, Focusable, Focused},
+    lenses::{AudioVolumeLens, TextSectionsColorLens},
+    pause::{PauseState, PAUSED_MUSIC_VOLUME_FACTOR},
+    theme::{ThemedBackground, UiTheme},
+    tween::fade_in,
+    widgets::{Checkbox, Dropdown, Slider, Toggle, WidgetCommands},
+    GameState, Music, MENU_BUTTON_COOLDOWN_SECS,
+};
+
+/// How much left/right changes a volume slider.
+static VOLUME_STEP: f32 = 0.05;
+static SCREEN_FADE_IN_SECS: f32 = 0.2;
+static MUSIC_FADE_IN_SECS: f32 = 2.0;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .init_resource::<SettingsReturnFocus>()
+            .add_state::<SettingsState>()
+            .add_event::<OpenSettings>()
+            .add_systems(OnEnter(GameState::Menu), spawn_menu_settings_button)
+            .add_systems(OnExit(GameState::Menu), despawn_menu_settings_button)
+            .add_systems(OnEnter(SettingsState::Open), spawn_settings_screen)
+            .add_systems(OnExit(SettingsState::Open), despawn_settings_screen)
+            .add_systems(
+                Update,
+                (
+                    open_settings,
+                    close_settings,
+                    sync_settings_from_widgets,
+                    apply_window_settings,
+                    apply_audio_settings,
+                    apply_quality_settings,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(States, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub enum SettingsState {
+    #[default]
+    Closed,
+    Open,
+}
+
+/// Sent to open the settings screen, e.g. from the pause menu.
+#[derive(Event)]
+pub struct OpenSettings;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Quality {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Quality {
+    pub const ALL: [Quality; 3] = [Quality::Low, Quality::Medium, Quality::High];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Quality::Low => "Low",
+            Quality::Medium => "Medium",
+            Quality::High => "High",
+        }
+    }
+}
+
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    pub muted: bool,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub quality: Quality,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            effects_volume: 1.0,
+            muted: false,
+            fullscreen: false,
+            vsync: true,
+            quality: Quality::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn effective_master_volume(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master_volume
+        }
+    }
+}
+
+/// Which setting a widget on the settings screen edits.
+#[derive(Component, Clone, Copy)]
+enum SettingsField {
+    MasterVolume,
+    MusicVolume,
+    EffectsVolume,
+    Muted,
+    Fullscreen,
+    Vsync,
+    Quality,
+}
+
+#[derive(Component)]
+struct SettingsScreen;
+
+#[derive(Component)]
+struct SettingsBackButton;
+
+#[derive(Component)]
+struct MenuSettings;
+
+#[derive(Component)]
+struct MenuSettingsButton;
+
+/// What was focused before the settings screen opened.
+#[derive(Resource, Default)]
+struct SettingsReturnFocus(Option<Entity>);
+
+fn spawn_menu_settings_button(mut commands: Commands, theme: Res<UiTheme>) {
+    let button = commands
+        .text_button(&theme, "Settings")
+        .size(ButtonSize::Medium)
+        .spawn()
+        .insert((
+            MenuSettingsButton,
+            Focusable { order: 1 },
+            ButtonCooldown::from_secs(MENU_BUTTON_COOLDOWN_SECS),
+        ))
+        .id();
+
+    commands
+        .spawn((
+            MenuSettings,
+            SafeAreaPadding,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    bottom: Val::Px(20.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .add_child(button);
+}
+
+fn despawn_menu_settings_button(
+    mut commands: Commands,
+    q_menu_settings: Query<Entity, With<MenuSettings>>,
+) {
+    for entity in q_menu_settings.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn open_settings(
+    mut open_settings: EventReader<OpenSettings>,
+    mut activated: EventReader<FocusActivated>,
+    q_menu_settings_button: Query<(), With<MenuSettingsButton>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+) {
+    let menu_button_activated = activated
+        .iter()
+        .any(|event| q_menu_settings_button.contains(event.0));
+
+    if open_settings.iter().next().is_some() || menu_button_activated {
+        next_settings_state.set(SettingsState::Open);
+    }
+}
+
+// checks the state itself instead of using run_if, so back events don't pile up while closed
+fn close_settings(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut back: EventReader<FocusBack>,
+    mut activated: EventReader<FocusActivated>,
+    q_back_button: Query<(), With<SettingsBackButton>>,
+    settings_state: Res<State<SettingsState>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+) {
+    let back_pressed = back.iter().next().is_some();
+    let back_button_activated = activated
+        .iter()
+        .any(|event| q_back_button.contains(event.0));
+
+    if settings_state.get() == &SettingsState::Open
+        && (back_pressed || back_button_activated || keyboard_input.just_pressed(KeyCode::Escape))
+    {
+        next_settings_state.set(SettingsState::Closed);
+    }
+}
+
+fn settings_row(
+    commands: &mut Commands,
+    theme: &UiTheme,
+    label: &str,
+    widget: Entity,
+    fade_in: bool,
+) -> Entity {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(420.0),
+                margin: UiRect::vertical(Val::Px(6.0)),
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            let mut text = parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 28.0,
+                    color: theme.text,
+                    ..default()
+                },
+            ));
+            if fade_in {
+                text.insert(Animator::new(Tween::new(
+                    EaseFunction::QuadraticOut,
+                    Duration::from_secs_f32(SCREEN_FADE_IN_SECS),
+                    TextSectionsColorLens {
+                        start: theme.text.with_a(0.0),
+                        end: theme.text,
+                    },
+                )));
+            }
+        })
+        .add_child(widget)
+        .id()
+}
+
+fn spawn_settings_screen(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    theme: Res<UiTheme>,
+    time: Res<Time>,
+    mut focused: ResMut<Focused>,
+    mut return_focus: ResMut<SettingsReturnFocus>,
+) {
+    // tweens advance by `Time::delta`, which is frozen on the pause menu
+    let fade_in = !time.is_paused();
+    let quality_options = Quality::ALL.map(|quality| quality.name());
+    let selected_quality = Quality::ALL
+        .iter()
+        .position(|quality| *quality == settings.quality)
+        .unwrap_or_default();
+    // the browser decides these
+    let window_settings_disabled = cfg!(target_arch = "wasm32");
+
+    let widgets = [
+        (
+            "Volume",
+            commands
+                .slider(&theme, settings.master_volume)
+                .step(VOLUME_STEP)
+                .spawn()
+                .insert(SettingsField::MasterVolume)
+                .id(),
+        ),
+        (
+            "Music",
+            commands
+                .slider(&theme, settings.music_volume)
+                .step(VOLUME_STEP)
+                .spawn()
+                .insert(SettingsField::MusicVolume)
+                .id(),
+        ),
+        (
+            "Effects",
+            commands
+                .slider(&theme, settings.effects_volume)
+                .step(VOLUME_STEP)
+                .spawn()
+                .insert(SettingsField::EffectsVolume)
+                .id(),
+        ),
+        (
+            "Mute",
+            commands
+                .checkbox(&theme, settings.muted)
+                .spawn()
+                .insert(SettingsField::Muted)
+                .id(),
+        ),
+        (
+            "Fullscreen",
+            commands
+                .toggle(&theme, settings.fullscreen)
+                .disabled(window_settings_disabled)
+                .spawn()
+                .insert(SettingsField::Fullscreen)
+                .id(),
+        ),
+        (
+            "VSync",
+            commands
+                .toggle(&theme, settings.vsync)
+                .disabled(window_settings_disabled)
+                .spawn()
+                .insert(SettingsField::Vsync)
+                .id(),
+        ),
+        (
+            "Quality",
+            commands
+                .dropdown(&theme, &quality_options)
+                .selected(selected_quality)
+                .spawn()
+                .insert(SettingsField::Quality)
+                .id(),
+        ),
+    ];
+
+    let mut rows = vec![];
+    for (order, (label, widget)) in widgets.iter().enumerate() {
+        commands.entity(*widget).insert(Focusable {
+            order: order as i32,
+        });
+        rows.push(settings_row(&mut commands, &theme, label, *widget, fade_in));
+    }
+
+    let back_button = commands
+        .text_button(&theme, "Back")
+        .size(ButtonSize::Medium)
+        .spawn()
+        .insert((
+            SettingsBackButton,
+            Focusable {
+                order: widgets.len() as i32,
+            },
+        ))
+        .id();
+    rows.push(back_button);
+
+    return_focus.0 = focused.0;
+    focused.0 = Some(widgets[0].1);
+
+    let panel = commands.panel(&theme).spawn().push_children(&rows).id();
+
+    let mut screen = commands.spawn((
+        SettingsScreen,
+        FocusTrap,
+        SafeAreaPadding,
+        ThemedBackground::Overlay,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: theme.overlay.into(),
+            focus_policy: FocusPolicy::Block,
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+    ));
+    screen.add_child(panel);
+    if fade_in {
+        screen.insert(Animator::new(fade_in(theme.overlay, SCREEN_FADE_IN_SECS)));
+    }
+}
+
+fn despawn_settings_screen(
+    mut commands: Commands,
+    q_settings_screen: Query<Entity, With<SettingsScreen>>,
+    mut focused: ResMut<Focused>,
+    return_focus: Res<SettingsReturnFocus>,
+) {
+    for entity in q_settings_screen.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    focused.0 = return_focus.0;
+}
+
+fn sync_settings_from_widgets(
+    q_slider: Query<(&Slider, &SettingsField), Changed<Slider>>,
+    q_toggle: Query<(&Toggle, &SettingsField), Changed<Toggle>>,
+    q_checkbox: Query<(&Checkbox, &SettingsField), Changed<Checkbox>>,
+    q_dropdown: Query<(&Dropdown, &SettingsField), Changed<Dropdown>>,
+    mut settings: ResMut<Settings>,
+) {
+    let mut new_settings = settings.clone();
+
+    for (slider, field) in q_slider.iter() {
+        match field {
+            SettingsField::MasterVolume => new_settings.master_volume = slider.value,
+            SettingsField::MusicVolume => new_settings.music_volume = slider.value,
+            SettingsField::EffectsVolume => new_settings.effects_volume = slider.value,
+            _ => {}
+        }
+    }
+
+    for (toggle, field) in q_toggle.iter() {
+        match field {
+            SettingsField::Fullscreen => new_settings.fullscreen = toggle.on,
+            SettingsField::Vsync => new_settings.vsync = toggle.on,
+            _ => {}
+        }
+    }
+
+    for (checkbox, field) in q_checkbox.iter() {
+        if let SettingsField::Muted = field {
+            new_settings.muted = checkbox.checked;
+        }
+    }
+
+    for (dropdown, field) in q_dropdown.iter() {
+        if let SettingsField::Quality = field {
+            if let Some(quality) = Quality::ALL.get(dropdown.selected) {
+                new_settings.quality = *quality;
+            }
+        }
+    }
+
+    settings.set_if_neq(new_settings);
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut q_window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for mut window in q_window.iter_mut() {
+        window.mode = if settings.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+        window.present_mode = if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+}
+
+/// Sets every sink to the master volume times its bus volume. `GlobalVolume` is left
+/// alone, so the master volume isn't applied twice.
+///
+/// Music fades in when it starts. Changing the volumes meanwhile cuts the fade short.
+fn apply_audio_settings(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    pause_state: Res<State<PauseState>>,
+    mut applied: Local<Option<(f32, f32)>>,
+    q_sink: Query<(
+        Entity,
+        Ref<AudioSink>,
+        Option<&Music>,
+        Option<&Animator<AudioSink>>,
+    )>,
+) {
+    let master_volume = settings.effective_master_volume();
+    // music is ducked while paused
+    let music_volume = match pause_state.get() {
+        PauseState::Running => settings.music_volume,
+        PauseState::Paused => settings.music_volume * PAUSED_MUSIC_VOLUME_FACTOR,
+    };
+    let volumes = (
+        master_volume * music_volume,
+        master_volume * settings.effects_volume,
+    );
+    // changing other settings leaves the sinks alone
+    let volumes_changed = *applied != Some(volumes);
+    *applied = Some(volumes);
+
+    let (music_volume, effects_volume) = volumes;
+    for (entity, sink, music, animator) in q_sink.iter() {
+        if sink.is_added() && music.is_some() {
+            sink.set_volume(0.0);
+            commands.entity(entity).insert(Animator::new(Tween::new(
+                EaseFunction::QuadraticIn,
+                Duration::from_secs_f32(MUSIC_FADE_IN_SECS),
+                AudioVolumeLens {
+                    start: 0.0,
+                    end: music_volume,
+                },
+            )));
+            continue;
+        }
+        if !volumes_changed && !sink.is_added() {
+            continue;
+        }
+        if animator.is_some() {
+            commands.entity(entity).remove::<Animator<AudioSink>>();
+        }
+        sink.set_volume(if music.is_some() {
+            music_volume
+        } else {
+            effects_volume
+        });
+    }
+}
+
+// shadows are set by `apply_lighting`, and post-processing by `apply_post_process`
+fn apply_quality_settings(settings: Res<Settings>, mut msaa: ResMut<Msaa>) {
+    if settings.is_changed() {
+        *msaa = match settings.quality {
+            Quality::Low => Msaa::Off,
+            Quality::Medium | Quality::High => Msaa::Sample4,
+        };
+    }
+}
@@ -1,20 +1,25 @@
 use bevy::{
     self,
-    prelude::Image,
+    prelude::{Color, Component, Image},
     render::render_resource::{Extent3d, TextureDimension, TextureFormat},
 };
 use image::{ImageBuffer, Rgba};
 use imageproc::drawing::draw_text_mut;
 use rusttype::{Font, Scale};
 
-pub fn text_to_image(text: &str) -> Image {
+/// Marks an entity whose material texture was made by `text_to_image` from this text,
+/// so it can be redrawn when the theme changes.
+#[derive(Component)]
+pub struct TextTexture(pub String);
+
+pub fn text_to_image(text: &str, background: Color) -> Image {
     let height = 100;
     let width = 200;
     let mut image_buffer = ImageBuffer::new(width, height);
 
-    // fill with red
+    // fill with background color
     for pixel in image_buffer.pixels_mut() {
-        *pixel = Rgba([255u8, 0u8, 0u8, 255u8]);
+        *pixel = Rgba(background.as_rgba_u8());
     }
 
     let font = Font::try_from_bytes(include_bytes!("../assets/Nunito-Regular.ttf")).unwrap();
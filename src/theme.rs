@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+use bevy_tweening::AnimationSystem;
+
+use crate::{
+    text_to_image::{text_to_image, TextTexture},
+    utils::AssetHandle,
+    BackgroundPlane, PrimaryColorHue,
+};
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PrimaryColorHue>()
+            .register_type::<ThemeSettings>()
+            .init_resource::<ThemeSettings>()
+            .init_resource::<Palette>()
+            .init_resource::<UiTheme>()
+            .add_systems(PreUpdate, update_theme)
+            .add_systems(
+                Update,
+                (
+                    recolor_background,
+                    // so a fade-in tween overrides the color on the first frame
+                    recolor_ui_backgrounds.before(AnimationSystem::AnimationUpdate),
+                    recolor_text_textures,
+                ),
+            );
+    }
+}
+
+/// Saturation and lightness of the primary color; the other colors are derived from them.
+/// Edit these or `PrimaryColorHue` at runtime (e.g. from the inspector) to recolor everything.
+#[derive(Reflect, Resource, Clone, Copy)]
+#[reflect(Resource)]
+pub struct ThemeSettings {
+    pub saturation: f32,
+    pub lightness: f32,
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            saturation: 0.5,
+            lightness: 0.4,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Palette {
+    pub primary: Color,
+    pub secondary: Color,
+    pub background: Color,
+    pub text: Color,
+    pub accent: Color,
+}
+
+impl Palette {
+    pub fn new(hue: f32, settings: &ThemeSettings) -> Self {
+        let hue = hue * 360.0;
+        let hsl = |hue_offset: f32, saturation: f32, lightness: f32| {
+            Color::hsl(
+                (hue + hue_offset).rem_euclid(360.0),
+                saturation.clamp(0.0, 1.0),
+                lightness.clamp(0.0, 1.0),
+            )
+        };
+        let (s, l) = (settings.saturation, settings.lightness);
+
+        Self {
+            primary: hsl(0.0, s, l),
+            secondary: hsl(30.0, s, l),
+            background: hsl(0.0, s * 0.4, l * 0.5),
+            text: Color::WHITE,
+            accent: hsl(180.0, s, l + 0.1),
+        }
+    }
+}
+
+impl FromWorld for Palette {
+    fn from_world(world: &mut World) -> Self {
+        let hue = world
+            .get_resource::<PrimaryColorHue>()
+            .map(|hue| hue.0)
+            .unwrap_or_default();
+        let settings = world
+            .get_resource::<ThemeSettings>()
+            .copied()
+            .unwrap_or_default();
+        Palette::new(hue, &settings)
+    }
+}
+
+/// Colors shared by all widgets, derived from the `Palette`.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct UiTheme {
+    pub button: Color,
+    pub button_hover: Color,
+    pub button_pressed: Color,
+    pub button_disabled: Color,
+    pub border: Color,
+    pub text: Color,
+    pub text_shadow: Color,
+    pub panel: Color,
+    pub overlay: Color,
+}
+
+impl UiTheme {
+    pub fn new(palette: &Palette) -> Self {
+        let [h, s, l, _] = palette.primary.as_hsla_f32();
+
+        Self {
+            button: palette.primary,
+            button_hover: Color::hsl(h, s, (l + 0.05).min(1.0)),
+            button_pressed: Color::hsl(h, s, (l - 0.05).max(0.0)),
+            button_disabled: Color::hsl(h, s * 0.2, l * 0.75),
+            border: Color::BLACK.with_a(0.5),
+            text: palette.text,
+            text_shadow: Color::BLACK.with_a(0.5),
+            panel: Color::hsl(h, s * 0.4, l * 0.375),
+            overlay: Color::BLACK.with_a(0.6),
+        }
+    }
+}
+
+impl FromWorld for UiTheme {
+    fn from_world(world: &mut World) -> Self {
+        let palette = Palette::from_world(world);
+        UiTheme::new(&palette)
+    }
+}
+
+/// Keeps a UI node's `BackgroundColor` in sync with the theme.
+#[derive(Component, Clone, Copy)]
+pub enum ThemedBackground {
+    Panel,
+    Overlay,
+}
+
+impl ThemedBackground {
+    fn color(&self, theme: &UiTheme) -> Color {
+        match self {
+            ThemedBackground::Panel => theme.panel,
+            ThemedBackground::Overlay => theme.overlay,
+        }
+    }
+}
+
+fn update_theme(
+    primary_color_hue: Res<PrimaryColorHue>,
+    theme_settings: Res<ThemeSettings>,
+    mut palette: ResMut<Palette>,
+    mut theme: ResMut<UiTheme>,
+) {
+    if primary_color_hue.is_changed() || theme_settings.is_changed() {
+        *palette = Palette::new(primary_color_hue.0, &theme_settings);
+        *theme = UiTheme::new(&palette);
+    }
+}
+
+fn recolor_background(
+    palette: Res<Palette>,
+    background_material: Option<Res<AssetHandle<BackgroundPlane, StandardMaterial>>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(background_material) = background_material else {
+        return;
+    };
+
+    if palette.is_changed() || background_material.is_added() {
+        if let Some(material) = standard_materials.get_mut(&background_material.handle) {
+            material.base_color = palette.background;
+        }
+    }
+}
+
+fn recolor_ui_backgrounds(
+    theme: Res<UiTheme>,
+    mut q_node: Query<(Ref<ThemedBackground>, &mut BackgroundColor)>,
+) {
+    for (themed_background, mut background_color) in q_node.iter_mut() {
+        if theme.is_changed() || themed_background.is_added() {
+            background_color.0 = themed_background.color(&theme);
+        }
+    }
+}
+
+fn recolor_text_textures(
+    palette: Res<Palette>,
+    q_text_texture: Query<(&TextTexture, &Handle<StandardMaterial>)>,
+    standard_materials: Res<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !palette.is_changed() {
+        return;
+    }
+
+    for (text_texture, material_handle) in q_text_texture.iter() {
+        let texture = standard_materials
+            .get(material_handle)
+            .and_then(|material| material.base_color_texture.as_ref());
+        if let Some(image) = texture.and_then(|texture| images.get_mut(texture)) {
+            *image = text_to_image(&text_texture.0, palette.primary);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use bevy::{prelude::*, transform::TransformSystem};
+
+/// Runs gameplay in `FixedUpdate` and smooths the rendered `Transform` between fixed steps.
+///
+/// Gameplay systems go in `FixedUpdate` under `FixedGameplaySet::Gameplay` and write to
+/// `GameplayTransform`. The rendered `Transform` is overwritten every frame, so don't tween it
+/// on the same entity.
+pub struct FixedTimestepPlugin {
+    pub steps_per_second: f32,
+}
+
+impl Plugin for FixedTimestepPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FixedTime::new_from_secs(1.0 / self.steps_per_second))
+            .configure_sets(
+                FixedUpdate,
+                (FixedGameplaySet::SavePrevious, FixedGameplaySet::Gameplay).chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                save_previous_transforms.in_set(FixedGameplaySet::SavePrevious),
+            )
+            .add_systems(
+                PostUpdate,
+                interpolate_transforms.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum FixedGameplaySet {
+    SavePrevious,
+    Gameplay,
+}
+
+/// The canonical transform, only changed in `FixedUpdate`.
+#[derive(Component, Clone, Copy, Default)]
+pub struct GameplayTransform(pub Transform);
+
+/// `GameplayTransform` as of the previous fixed step.
+#[derive(Component, Clone, Copy, Default)]
+pub struct PreviousTransform(pub Transform);
+
+#[derive(Bundle, Default)]
+pub struct InterpolatedTransformBundle {
+    pub gameplay_transform: GameplayTransform,
+    pub previous_transform: PreviousTransform,
+}
+
+impl InterpolatedTransformBundle {
+    pub fn new(transform: Transform) -> Self {
+        Self {
+            gameplay_transform: GameplayTransform(transform),
+            previous_transform: PreviousTransform(transform),
+        }
+    }
+}
+
+/// How far we are between the last fixed step and the next one, in 0..=1.
+pub fn overstep_fraction(fixed_time: &FixedTime) -> f32 {
+    (fixed_time.accumulated().as_secs_f32() / fixed_time.period.as_secs_f32()).clamp(0.0, 1.0)
+}
+
+pub fn interpolate_transform(previous: &Transform, current: &Transform, alpha: f32) -> Transform {
+    Transform {
+        translation: previous.translation.lerp(current.translation, alpha),
+        rotation: previous.rotation.slerp(current.rotation, alpha),
+        scale: previous.scale.lerp(current.scale, alpha),
+    }
+}
+
+fn save_previous_transforms(mut q: Query<(&GameplayTransform, &mut PreviousTransform)>) {
+    for (gameplay_transform, mut previous_transform) in q.iter_mut() {
+        previous_transform.0 = gameplay_transform.0;
+    }
+}
+
+pub fn interpolate_transforms(
+    fixed_time: Res<FixedTime>,
+    mut q: Query<(&mut Transform, &GameplayTransform, &PreviousTransform)>,
+) {
+    let alpha = overstep_fraction(&fixed_time);
+
+    for (mut transform, gameplay_transform, previous_transform) in q.iter_mut() {
+        *transform = interpolate_transform(&previous_transform.0, &gameplay_transform.0, alpha);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    static PERIOD: Duration = Duration::from_millis(100);
+
+    fn move_right(mut q: Query<&mut GameplayTransform>) {
+        for mut gameplay_transform in q.iter_mut() {
+            gameplay_transform.0.translation.x += 1.0;
+        }
+    }
+
+    fn timestep_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(FixedTimestepPlugin {
+            steps_per_second: 10.0,
+        })
+        // exact, unlike the f32 period from steps_per_second
+        .insert_resource(FixedTime::new(PERIOD))
+        // zero delta, so fixed steps only run from `advance`
+        .init_resource::<Time>()
+        .add_systems(FixedUpdate, move_right.in_set(FixedGameplaySet::Gameplay));
+        app
+    }
+
+    /// Runs the fixed steps `delta` adds up to, then renders one frame.
+    fn advance(app: &mut App, delta: Duration) {
+        app.world.resource_mut::<FixedTime>().tick(delta);
+        while app.world.resource_mut::<FixedTime>().expend().is_ok() {
+            app.world.run_schedule(FixedUpdate);
+        }
+        app.update();
+    }
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn overstep_fraction_is_clamped() {
+        let mut fixed_time = FixedTime::new(PERIOD);
+        assert_eq!(overstep_fraction(&fixed_time), 0.0);
+        fixed_time.tick(Duration::from_millis(25));
+        assert_near(overstep_fraction(&fixed_time), 0.25);
+        fixed_time.tick(Duration::from_millis(200));
+        assert_eq!(overstep_fraction(&fixed_time), 1.0);
+    }
+
+    #[test]
+    fn transform_is_interpolated_between_fixed_steps() {
+        let mut app = timestep_app();
+        let entity = app
+            .world
+            .spawn((
+                Transform::default(),
+                InterpolatedTransformBundle::new(Transform::default()),
+            ))
+            .id();
+        let rendered_x = |app: &App| app.world.get::<Transform>(entity).unwrap().translation.x;
+
+        // two steps, then halfway to the third
+        advance(&mut app, Duration::from_millis(250));
+        assert_eq!(
+            app.world
+                .get::<GameplayTransform>(entity)
+                .unwrap()
+                .0
+                .translation
+                .x,
+            2.0
+        );
+        assert_eq!(
+            app.world
+                .get::<PreviousTransform>(entity)
+                .unwrap()
+                .0
+                .translation
+                .x,
+            1.0
+        );
+        assert_near(overstep_fraction(app.world.resource::<FixedTime>()), 0.5);
+        assert_near(rendered_x(&app), 1.5);
+
+        // no step, only further along
+        advance(&mut app, Duration::from_millis(30));
+        assert_near(overstep_fraction(app.world.resource::<FixedTime>()), 0.8);
+        assert_near(rendered_x(&app), 1.8);
+
+        // lands exactly on the next step
+        advance(&mut app, Duration::from_millis(20));
+        assert_near(overstep_fraction(app.world.resource::<FixedTime>()), 0.0);
+        assert_near(rendered_x(&app), 2.0);
+    }
+}
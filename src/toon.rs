@@ -0,0 +1,382 @@
+use bevy::{
+    asset::LoadState,
+    ecs::query::Has,
+    pbr::{MaterialPipeline, MaterialPipelineKey, NotShadowCaster},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    render::{
+        mesh::{skinning::SkinnedMesh, MeshVertexBufferLayout},
+        render_resource::{
+            AsBindGroup, Face, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+        },
+    },
+};
+
+use crate::{
+    scene_ready::{AttachToBone, AttachedToBone, SceneInstanceReady, SceneReadySet},
+    utils::AssetHandle,
+    warmup::{FailedShaders, ShaderWarmup, WarmupMaterialPlugin},
+};
+
+pub static TOON_SHADER_PATH: &str = "shaders/toon.wgsl";
+pub static TOON_OUTLINE_SHADER_PATH: &str = "shaders/toon_outline.wgsl";
+
+/// Cel shading for spawned glTF scenes: put `ToonShading` on a scene root, and once the
+/// scene is ready its `StandardMaterial`s are replaced by `ToonMaterial`s, with an
+/// inverted hull outline per mesh.
+///
+/// The original materials are kept, and put back when `ToonSettings::enabled` is off or
+/// the toon shader fails to load or compile. Outlines draw every mesh twice, so they are
+/// off by default on WebGL2. Props attached to the scene keep their own materials.
+///
+/// A `ShaderWarmup` scene is always converted, with outlines, whatever the settings, so
+/// turning them on later doesn't hitch.
+pub struct ToonPlugin;
+
+impl Plugin for ToonPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MaterialPlugin::<ToonMaterial>::default(),
+            MaterialPlugin::<OutlineMaterial>::default(),
+            WarmupMaterialPlugin::<ToonMaterial>::default(),
+            WarmupMaterialPlugin::<OutlineMaterial>::default(),
+        ))
+        .init_resource::<ToonSettings>()
+        .add_systems(Startup, load_toon_shaders)
+        .add_systems(
+            Update,
+            (
+                mark_toon_meshes.in_set(SceneReadySet::PostProcess),
+                sync_toon_meshes.after(SceneReadySet::PostProcess),
+            ),
+        );
+    }
+}
+
+#[derive(Resource, Clone, Copy)]
+pub struct ToonSettings {
+    pub enabled: bool,
+    pub outlines: bool,
+}
+
+impl Default for ToonSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            outlines: !cfg!(target_arch = "wasm32"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Outline {
+    pub color: Color,
+    /// World units.
+    pub width: f32,
+}
+
+/// Put on a scene root to cel shade every mesh in it.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+pub struct ToonShading {
+    /// Number of lit steps, at least 1.
+    pub bands: u32,
+    pub rim_color: Color,
+    /// Higher is a thinner rim.
+    pub rim_power: f32,
+    pub rim_strength: f32,
+    pub outline: Option<Outline>,
+}
+
+impl Default for ToonShading {
+    fn default() -> Self {
+        Self {
+            bands: 3,
+            rim_color: Color::WHITE,
+            rim_power: 4.0,
+            rim_strength: 0.3,
+            outline: Some(Outline {
+                color: Color::BLACK,
+                width: 0.01,
+            }),
+        }
+    }
+}
+
+#[derive(AsBindGroup, TypeUuid, TypePath, Clone)]
+#[uuid = "a4e2c9d1-7f3b-4e58-b6a0-2d91c5f8e374"]
+pub struct ToonMaterial {
+    #[uniform(0)]
+    pub base_color: Color,
+    #[uniform(0)]
+    pub rim_color: Color,
+    #[uniform(0)]
+    pub bands: f32,
+    #[uniform(0)]
+    pub rim_power: f32,
+    #[uniform(0)]
+    pub rim_strength: f32,
+    #[texture(1)]
+    #[sampler(2)]
+    pub base_color_texture: Option<Handle<Image>>,
+    pub alpha_mode: AlphaMode,
+}
+
+impl ToonMaterial {
+    pub fn new(original: &StandardMaterial, shading: &ToonShading) -> Self {
+        Self {
+            base_color: original.base_color,
+            rim_color: shading.rim_color,
+            bands: shading.bands.max(1) as f32,
+            rim_power: shading.rim_power,
+            rim_strength: shading.rim_strength,
+            base_color_texture: original.base_color_texture.clone(),
+            alpha_mode: original.alpha_mode,
+        }
+    }
+}
+
+impl Material for ToonMaterial {
+    fn fragment_shader() -> ShaderRef {
+        TOON_SHADER_PATH.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+}
+
+#[derive(AsBindGroup, TypeUuid, TypePath, Clone)]
+#[uuid = "3c7d5b18-e9a2-4f06-8b4d-6a1f0e2c9b57"]
+pub struct OutlineMaterial {
+    #[uniform(0)]
+    pub color: Color,
+    #[uniform(0)]
+    pub width: f32,
+}
+
+impl Material for OutlineMaterial {
+    fn vertex_shader() -> ShaderRef {
+        TOON_OUTLINE_SHADER_PATH.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        TOON_OUTLINE_SHADER_PATH.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // only the back faces of the pushed out hull show, around the mesh
+        descriptor.primitive.cull_mode = Some(Face::Front);
+        Ok(())
+    }
+}
+
+/// A mesh of a `ToonShading` scene, with the material it was spawned with.
+#[derive(Component, Clone)]
+pub struct ToonMesh {
+    pub original: Handle<StandardMaterial>,
+    pub shading: ToonShading,
+}
+
+/// The outline hull of a `ToonMesh`, a child of it.
+#[derive(Component)]
+pub struct ToonOutline;
+
+fn load_toon_shaders(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(AssetHandle::<ToonMaterial, Shader>::new(
+        asset_server.load(TOON_SHADER_PATH),
+    ));
+    commands.insert_resource(AssetHandle::<OutlineMaterial, Shader>::new(
+        asset_server.load(TOON_OUTLINE_SHADER_PATH),
+    ));
+}
+
+fn spawn_outline(
+    commands: &mut Commands,
+    outline_materials: &mut Assets<OutlineMaterial>,
+    entity: Entity,
+    mesh: &Handle<Mesh>,
+    skinned_mesh: Option<&SkinnedMesh>,
+    style: Outline,
+) {
+    let mut hull = commands.spawn((
+        ToonOutline,
+        NotShadowCaster,
+        MaterialMeshBundle {
+            mesh: mesh.clone(),
+            material: outline_materials.add(OutlineMaterial {
+                color: style.color,
+                width: style.width,
+            }),
+            ..default()
+        },
+    ));
+    // skinned hulls follow the same joints as the mesh
+    if let Some(skinned_mesh) = skinned_mesh {
+        hull.insert(skinned_mesh.clone());
+    }
+    let hull = hull.id();
+    commands.entity(entity).add_child(hull);
+}
+
+/// The descendants of a scene root, without the props attached to it.
+fn scene_descendants(
+    entity: Entity,
+    q_children: &Query<&Children>,
+    q_prop: &Query<(), Or<(With<AttachToBone>, With<AttachedToBone>)>>,
+    descendants: &mut Vec<Entity>,
+) {
+    for child in q_children.get(entity).into_iter().flatten() {
+        if !q_prop.contains(*child) {
+            descendants.push(*child);
+            scene_descendants(*child, q_children, q_prop, descendants);
+        }
+    }
+}
+
+fn mark_toon_meshes(
+    mut commands: Commands,
+    mut ready: EventReader<SceneInstanceReady>,
+    standard_materials: Res<Assets<StandardMaterial>>,
+    mut toon_materials: ResMut<Assets<ToonMaterial>>,
+    mut outline_materials: ResMut<Assets<OutlineMaterial>>,
+    q_shading: Query<(&ToonShading, Has<ShaderWarmup>)>,
+    q_children: Query<&Children>,
+    q_prop: Query<(), Or<(With<AttachToBone>, With<AttachedToBone>)>>,
+    q_mesh: Query<(
+        &Handle<StandardMaterial>,
+        &Handle<Mesh>,
+        Option<&SkinnedMesh>,
+    )>,
+) {
+    let mut descendants = Vec::new();
+    for SceneInstanceReady { root } in ready.iter() {
+        let Ok((shading, warmup)) = q_shading.get(*root) else {
+            continue;
+        };
+        descendants.clear();
+        scene_descendants(*root, &q_children, &q_prop, &mut descendants);
+
+        for entity in descendants.iter().copied() {
+            let Ok((material, mesh, skinned_mesh)) = q_mesh.get(entity) else {
+                continue;
+            };
+            if !warmup {
+                commands.entity(entity).insert(ToonMesh {
+                    original: material.clone(),
+                    shading: *shading,
+                });
+                continue;
+            }
+
+            // only compiles the pipelines, and is despawned after that
+            let Some(original) = standard_materials.get(material) else {
+                continue;
+            };
+            commands
+                .entity(entity)
+                .remove::<Handle<StandardMaterial>>()
+                .insert(toon_materials.add(ToonMaterial::new(original, shading)));
+            if let Some(style) = shading.outline {
+                spawn_outline(
+                    &mut commands,
+                    &mut outline_materials,
+                    entity,
+                    mesh,
+                    skinned_mesh,
+                    style,
+                );
+            }
+        }
+    }
+}
+
+fn sync_toon_meshes(
+    mut commands: Commands,
+    settings: Res<ToonSettings>,
+    asset_server: Res<AssetServer>,
+    failed_shaders: Res<FailedShaders>,
+    toon_shader: Res<AssetHandle<ToonMaterial, Shader>>,
+    outline_shader: Res<AssetHandle<OutlineMaterial, Shader>>,
+    standard_materials: Res<Assets<StandardMaterial>>,
+    mut toon_materials: ResMut<Assets<ToonMaterial>>,
+    mut outline_materials: ResMut<Assets<OutlineMaterial>>,
+    q_mesh: Query<(
+        Entity,
+        &ToonMesh,
+        &Handle<Mesh>,
+        Option<&SkinnedMesh>,
+        Option<&Handle<ToonMaterial>>,
+        Option<&Children>,
+    )>,
+    q_added: Query<Entity, Added<ToonMesh>>,
+    q_outline: Query<(), With<ToonOutline>>,
+    mut applied: Local<Option<(bool, bool)>>,
+    mut waiting: Local<Vec<Entity>>,
+) {
+    let failed = |handle: &Handle<Shader>| {
+        asset_server.get_load_state(handle) == LoadState::Failed || failed_shaders.contains(handle)
+    };
+    let toon = settings.enabled && !failed(&toon_shader.handle);
+    let outlines = toon && settings.outlines && !failed(&outline_shader.handle);
+
+    // only new meshes and ones waiting for their material, unless the settings or the
+    // shaders changed
+    let changed = *applied != Some((toon, outlines));
+    *applied = Some((toon, outlines));
+    let entities: Vec<Entity> = if changed {
+        waiting.clear();
+        q_mesh.iter().map(|(entity, ..)| entity).collect()
+    } else {
+        waiting.drain(..).chain(q_added.iter()).collect()
+    };
+
+    for (entity, toon_mesh, mesh, skinned_mesh, toon_material, children) in
+        q_mesh.iter_many(&entities)
+    {
+        let outline = children.and_then(|children| {
+            children
+                .iter()
+                .find(|child| q_outline.contains(**child))
+                .copied()
+        });
+
+        if toon && toon_material.is_none() {
+            let Some(original) = standard_materials.get(&toon_mesh.original) else {
+                waiting.push(entity);
+                continue;
+            };
+            commands
+                .entity(entity)
+                .remove::<Handle<StandardMaterial>>()
+                .insert(toon_materials.add(ToonMaterial::new(original, &toon_mesh.shading)));
+        } else if !toon && toon_material.is_some() {
+            commands
+                .entity(entity)
+                .remove::<Handle<ToonMaterial>>()
+                .insert(toon_mesh.original.clone());
+        }
+
+        let wants_outline = outlines && toon_mesh.shading.outline.is_some();
+        match (wants_outline, outline, toon_mesh.shading.outline) {
+            (true, None, Some(style)) => {
+                spawn_outline(
+                    &mut commands,
+                    &mut outline_materials,
+                    entity,
+                    mesh,
+                    skinned_mesh,
+                    style,
+                );
+            }
+            (false, Some(outline), _) => {
+                commands.entity(outline).despawn_recursive();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+use bevy_tweening::{EaseFunction, Lens, Tween};
+use std::{f32::consts::PI, time::Duration};
+
+use crate::{focus::InputBlocked, theme::Palette, BackgroundPlane, GameState};
+
+/// Switches `GameState` behind a screen effect: the exit half covers the screen, the
+/// state changes at the midpoint, and the enter half reveals it again.
+///
+/// Send `TransitionTo` instead of setting `NextState<GameState>` directly.
+/// Input is blocked until the transition is over.
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TransitionSettings>()
+            .init_resource::<ActiveTransition>()
+            .add_event::<TransitionTo>()
+            .add_systems(Update, (start_transition, advance_transition).chain());
+    }
+}
+
+#[derive(Event)]
+pub struct TransitionTo(pub GameState);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TransitionEffect {
+    /// Fades to the background color.
+    #[default]
+    Fade,
+    /// Slides a panel in from the left, and out to the right.
+    Wipe,
+    /// Shrinks and rotates the background plane, like its intro.
+    Spin,
+}
+
+#[derive(Resource, Clone, Copy)]
+pub struct TransitionSettings {
+    pub effect: TransitionEffect,
+    /// Seconds, for both halves together.
+    pub duration: f32,
+}
+
+impl Default for TransitionSettings {
+    fn default() -> Self {
+        Self {
+            effect: TransitionEffect::default(),
+            duration: 0.6,
+        }
+    }
+}
+
+impl TransitionSettings {
+    /// Eases into the look of a new state, e.g. its lighting, as the second half of a
+    /// transition reveals it. Start it when the state changes.
+    pub fn reveal_tween<T: Component>(
+        &self,
+        lens: impl Lens<T> + Send + Sync + 'static,
+    ) -> Tween<T> {
+        Tween::new(
+            EaseFunction::QuadraticInOut,
+            Duration::from_secs_f32((self.duration / 2.0).max(0.01)),
+            lens,
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TransitionStep {
+    Exiting,
+    /// Returned once, when the state should change.
+    Switch,
+    Entering,
+    Finished,
+}
+
+/// Progress of one transition, without any effect on the world.
+#[derive(Clone, Debug)]
+pub struct ScreenTransition {
+    pub target: GameState,
+    pub effect: TransitionEffect,
+    pub duration: f32,
+    elapsed: f32,
+    switched: bool,
+}
+
+impl ScreenTransition {
+    pub fn new(target: GameState, settings: &TransitionSettings) -> Self {
+        Self {
+            target,
+            effect: settings.effect,
+            duration: settings.duration.max(0.0),
+            elapsed: 0.0,
+            switched: false,
+        }
+    }
+
+    pub fn is_entering(&self) -> bool {
+        self.switched
+    }
+
+    pub fn tick(&mut self, delta: f32) -> TransitionStep {
+        self.elapsed += delta;
+
+        if !self.switched && self.elapsed >= self.duration / 2.0 {
+            self.switched = true;
+            return TransitionStep::Switch;
+        }
+        if self.switched && self.elapsed >= self.duration {
+            TransitionStep::Finished
+        } else if self.switched {
+            TransitionStep::Entering
+        } else {
+            TransitionStep::Exiting
+        }
+    }
+
+    /// How much of the screen is covered, from 0 to 1 and back.
+    pub fn coverage(&self) -> f32 {
+        let half = self.duration / 2.0;
+        if half <= 0.0 {
+            return 0.0;
+        }
+        let smoothstep = |t: f32| {
+            let t = t.clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        };
+        if self.switched {
+            1.0 - smoothstep((self.elapsed - half) / half)
+        } else {
+            smoothstep(self.elapsed / half)
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ActiveTransition(pub Option<ScreenTransition>);
+
+#[derive(Component)]
+struct TransitionOverlay;
+
+/// The transform the background plane parent had when a `Spin` transition started.
+#[derive(Component)]
+struct SpinBaseTransform(Transform);
+
+fn start_transition(
+    mut commands: Commands,
+    mut transition_to: EventReader<TransitionTo>,
+    settings: Res<TransitionSettings>,
+    state: Res<State<GameState>>,
+    mut active: ResMut<ActiveTransition>,
+    mut input_blocked: ResMut<InputBlocked>,
+) {
+    for TransitionTo(target) in transition_to.iter() {
+        if active.0.is_some() {
+            warn!("already transitioning, ignoring transition to {:?}", target);
+            continue;
+        }
+        if state.get() == target {
+            continue;
+        }
+
+        active.0 = Some(ScreenTransition::new(target.clone(), &settings));
+        input_blocked.0 = true;
+        commands.spawn((
+            TransitionOverlay,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                background_color: Color::NONE.into(),
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn advance_transition(
+    mut commands: Commands,
+    time: Res<Time>,
+    palette: Res<Palette>,
+    mut active: ResMut<ActiveTransition>,
+    mut input_blocked: ResMut<InputBlocked>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut q_overlay: Query<(Entity, &mut Style, &mut BackgroundColor), With<TransitionOverlay>>,
+    q_background_plane_parent: Query<&Parent, With<BackgroundPlane>>,
+    mut q_transform: Query<(&mut Transform, Option<&SpinBaseTransform>)>,
+) {
+    let Some(transition) = active.0.as_mut() else {
+        return;
+    };
+
+    // keeps going while the game is paused, e.g. when quitting from the pause menu
+    let step = transition.tick(time.raw_delta_seconds());
+    if step == TransitionStep::Switch {
+        next_state.set(transition.target.clone());
+    }
+
+    let coverage = if step == TransitionStep::Finished {
+        0.0
+    } else {
+        transition.coverage()
+    };
+    for (_, mut style, mut background_color) in q_overlay.iter_mut() {
+        match transition.effect {
+            TransitionEffect::Fade => {
+                background_color.0 = palette.background.with_a(coverage);
+            }
+            TransitionEffect::Wipe => {
+                background_color.0 = palette.background;
+                style.width = Val::Percent(coverage * 100.0);
+                // enter from the left, leave to the right
+                if transition.is_entering() {
+                    style.left = Val::Auto;
+                    style.right = Val::Px(0.0);
+                } else {
+                    style.left = Val::Px(0.0);
+                    style.right = Val::Auto;
+                }
+            }
+            TransitionEffect::Spin => {}
+        }
+    }
+    if transition.effect == TransitionEffect::Spin {
+        for parent in q_background_plane_parent.iter() {
+            let Ok((mut transform, spin_base)) = q_transform.get_mut(parent.get()) else {
+                continue;
+            };
+            let base = match spin_base {
+                Some(SpinBaseTransform(base)) => *base,
+                None => {
+                    commands
+                        .entity(parent.get())
+                        .insert(SpinBaseTransform(*transform));
+                    *transform
+                }
+            };
+            transform.scale = base.scale * (1.0 - coverage).max(0.0001);
+            transform.rotation = base.rotation * Quat::from_rotation_z(-PI / 2.0 * coverage);
+            if step == TransitionStep::Finished {
+                commands.entity(parent.get()).remove::<SpinBaseTransform>();
+            }
+        }
+    }
+
+    if step == TransitionStep::Finished {
+        active.0 = None;
+        input_blocked.0 = false;
+        for (entity, _, _) in q_overlay.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::ThemeSettings;
+    use std::time::Duration;
+
+    fn settings(effect: TransitionEffect) -> TransitionSettings {
+        TransitionSettings {
+            effect,
+            duration: 1.0,
+        }
+    }
+
+    #[test]
+    fn switches_once_at_the_midpoint() {
+        let mut transition =
+            ScreenTransition::new(GameState::Menu, &settings(TransitionEffect::Fade));
+
+        assert_eq!(transition.tick(0.25), TransitionStep::Exiting);
+        assert!(transition.coverage() > 0.0 && transition.coverage() < 1.0);
+        assert_eq!(transition.tick(0.25), TransitionStep::Switch);
+        assert_eq!(transition.coverage(), 1.0);
+        assert!(transition.is_entering());
+        assert_eq!(transition.tick(0.25), TransitionStep::Entering);
+        assert_eq!(transition.tick(0.25), TransitionStep::Finished);
+        assert_eq!(transition.coverage(), 0.0);
+    }
+
+    #[test]
+    fn large_step_still_switches_before_finishing() {
+        let mut transition =
+            ScreenTransition::new(GameState::Menu, &settings(TransitionEffect::Fade));
+
+        assert_eq!(transition.tick(2.0), TransitionStep::Switch);
+        assert_eq!(transition.tick(0.0), TransitionStep::Finished);
+    }
+
+    fn transition_app(effect: TransitionEffect) -> App {
+        let mut app = App::new();
+        app.add_state::<GameState>()
+            .init_resource::<Time>()
+            .init_resource::<ActiveTransition>()
+            .init_resource::<InputBlocked>()
+            .insert_resource(settings(effect))
+            .insert_resource(Palette::new(0.0, &ThemeSettings::default()))
+            .add_event::<TransitionTo>()
+            .add_systems(Update, (start_transition, advance_transition).chain());
+        app
+    }
+
+    /// Lets `secs` of raw time pass for one update.
+    fn advance_time(app: &mut App, secs: f32) {
+        let mut time = app.world.resource_mut::<Time>();
+        if time.last_update().is_none() {
+            let startup = time.startup();
+            time.update_with_instant(startup);
+        }
+        let now = time.last_update().unwrap() + Duration::from_secs_f32(secs);
+        time.update_with_instant(now);
+        app.update();
+    }
+
+    fn state(app: &App) -> GameState {
+        app.world.resource::<State<GameState>>().get().clone()
+    }
+
+    #[test]
+    fn state_changes_at_the_midpoint_and_input_is_blocked_throughout() {
+        let mut app = transition_app(TransitionEffect::Fade);
+        app.world.send_event(TransitionTo(GameState::Menu));
+        advance_time(&mut app, 0.0);
+        assert!(app.world.resource::<InputBlocked>().0);
+
+        advance_time(&mut app, 0.4);
+        assert_eq!(state(&app), GameState::Loading);
+
+        // `NextState` is applied at the start of the following update
+        advance_time(&mut app, 0.2);
+        advance_time(&mut app, 0.0);
+        assert_eq!(state(&app), GameState::Menu);
+        assert!(app.world.resource::<InputBlocked>().0);
+
+        advance_time(&mut app, 0.3);
+        assert!(app.world.resource::<ActiveTransition>().0.is_some());
+
+        advance_time(&mut app, 0.2);
+        assert!(app.world.resource::<ActiveTransition>().0.is_none());
+        assert!(!app.world.resource::<InputBlocked>().0);
+        let mut q_overlay = app.world.query_filtered::<(), With<TransitionOverlay>>();
+        assert_eq!(q_overlay.iter(&app.world).count(), 0);
+    }
+
+    #[test]
+    fn spin_composes_with_the_background_transform() {
+        let mut app = transition_app(TransitionEffect::Spin);
+        let base = Transform::from_xyz(1.0, 2.0, 3.0)
+            .with_rotation(Quat::from_rotation_x(0.5))
+            .with_scale(Vec3::splat(2.0));
+        let parent = app
+            .world
+            .spawn(base)
+            .with_children(|parent| {
+                parent.spawn(BackgroundPlane);
+            })
+            .id();
+
+        app.world.send_event(TransitionTo(GameState::Menu));
+        advance_time(&mut app, 0.0);
+        advance_time(&mut app, 0.5);
+        let transform = *app.world.get::<Transform>(parent).unwrap();
+        assert_eq!(transform.translation, base.translation);
+        assert!(transform.scale.max_element() < 0.01);
+        assert!(transform
+            .rotation
+            .abs_diff_eq(base.rotation * Quat::from_rotation_z(-PI / 2.0), 1e-5));
+
+        advance_time(&mut app, 0.6);
+        let transform = *app.world.get::<Transform>(parent).unwrap();
+        assert_eq!(transform.scale, base.scale);
+        assert!(transform.rotation.abs_diff_eq(base.rotation, 1e-5));
+        assert!(app.world.get::<SpinBaseTransform>(parent).is_none());
+    }
+}
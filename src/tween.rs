@@ -0,0 +1,169 @@
+use bevy::{
+    ecs::{event::ManualEventReader, system::System},
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_tweening::{
+    asset_animator_system, component_animator_system, lens::*, AnimationSystem, BoxedTweenable,
+    Delay, EaseFunction, Sequence, Tracks, Tween, TweenCompleted,
+};
+use std::time::Duration;
+
+use crate::lenses::BackgroundColorLens;
+
+/// Presets and helpers on top of `bevy_tweening`, and completion callbacks.
+///
+/// A callback is registered in `TweenCallbacks`, which returns the user data to pass to
+/// `with_completed_event`. It runs once, the first time that tween completes.
+pub struct TweenHelpersPlugin;
+
+impl Plugin for TweenHelpersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TweenCallbacks>()
+            .add_systems(
+                Update,
+                (
+                    component_animator_system::<BackgroundColor>,
+                    component_animator_system::<AudioSink>,
+                    asset_animator_system::<StandardMaterial>,
+                )
+                    .in_set(AnimationSystem::AnimationUpdate),
+            )
+            .add_systems(PostUpdate, run_tween_callbacks);
+    }
+}
+
+type TweenCallback = Box<dyn FnOnce(&mut World, Entity) + Send + Sync>;
+
+#[derive(Resource)]
+pub struct TweenCallbacks {
+    next_id: u64,
+    callbacks: HashMap<u64, TweenCallback>,
+}
+
+impl Default for TweenCallbacks {
+    fn default() -> Self {
+        Self {
+            // keep clear of small hand-picked user data values
+            next_id: 1 << 32,
+            callbacks: HashMap::default(),
+        }
+    }
+}
+
+impl TweenCallbacks {
+    fn add(&mut self, callback: TweenCallback) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.callbacks.insert(id, callback);
+        id
+    }
+
+    /// Sends the event made from the animated entity.
+    pub fn send<E: Event>(
+        &mut self,
+        event: impl FnOnce(Entity) -> E + Send + Sync + 'static,
+    ) -> u64 {
+        self.add(Box::new(move |world, entity| {
+            world.resource_mut::<Events<E>>().send(event(entity));
+        }))
+    }
+
+    /// Runs a one-shot system, with the animated entity as input.
+    pub fn run<M>(&mut self, system: impl IntoSystem<Entity, (), M>) -> u64 {
+        let mut system = IntoSystem::into_system(system);
+        self.add(Box::new(move |world, entity| {
+            system.initialize(world);
+            system.run(entity, world);
+            system.apply_deferred(world);
+        }))
+    }
+}
+
+fn run_tween_callbacks(world: &mut World, mut reader: Local<ManualEventReader<TweenCompleted>>) {
+    let completed: Vec<(Entity, u64)> = reader
+        .iter(world.resource::<Events<TweenCompleted>>())
+        .map(|event| (event.entity, event.user_data))
+        .collect();
+
+    for (entity, id) in completed {
+        let callback = world.resource_mut::<TweenCallbacks>().callbacks.remove(&id);
+        if let Some(callback) = callback {
+            callback(world, entity);
+        }
+    }
+}
+
+/// A step that only waits, for use in a `Sequence`.
+pub fn wait<T: 'static>(secs: f32) -> Delay<T> {
+    Delay::new(Duration::from_secs_f32(secs))
+}
+
+/// Plays the tweenables one after the other.
+pub fn sequence<T: 'static>(
+    items: impl IntoIterator<Item = impl Into<BoxedTweenable<T>>>,
+) -> Sequence<T> {
+    Sequence::new(items)
+}
+
+/// Plays the tweenables at the same time.
+pub fn parallel<T: 'static>(
+    items: impl IntoIterator<Item = impl Into<BoxedTweenable<T>>>,
+) -> Tracks<T> {
+    Tracks::new(items)
+}
+
+/// Scales up from nothing with a bounce.
+pub fn pop_in(secs: f32) -> Tween<Transform> {
+    Tween::new(
+        EaseFunction::BounceOut,
+        Duration::from_secs_f32(secs),
+        TransformScaleLens {
+            start: Vec3::ZERO,
+            end: Vec3::ONE,
+        },
+    )
+}
+
+/// Scales up from nothing, fast at first.
+pub fn zoom_in(secs: f32) -> Tween<Transform> {
+    Tween::new(
+        EaseFunction::ExponentialOut,
+        Duration::from_secs_f32(secs),
+        TransformScaleLens {
+            // a zero scale would make the rotation of children undefined
+            start: Vec3::splat(0.0001),
+            end: Vec3::ONE,
+        },
+    )
+}
+
+/// Rotates into place, fast at first.
+pub fn spin_in(start: Quat, end: Quat, secs: f32) -> Tween<Transform> {
+    Tween::new(
+        EaseFunction::ExponentialOut,
+        Duration::from_secs_f32(secs),
+        TransformRotationLens { start, end },
+    )
+}
+
+/// Moves into place, fast at first.
+pub fn slide_in(start: Vec3, end: Vec3, secs: f32) -> Tween<Transform> {
+    Tween::new(
+        EaseFunction::QuarticOut,
+        Duration::from_secs_f32(secs),
+        TransformPositionLens { start, end },
+    )
+}
+
+/// Fades a UI node's background from transparent to `color`.
+pub fn fade_in(color: Color, secs: f32) -> Tween<BackgroundColor> {
+    Tween::new(
+        EaseFunction::QuadraticOut,
+        Duration::from_secs_f32(secs),
+        BackgroundColorLens {
+            start: color.with_a(0.0),
+            end: color,
+        },
+    )
+}
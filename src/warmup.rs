@@ -0,0 +1,274 @@
+use bevy::{
+    asset::HandleId,
+    pbr::RenderMaterials,
+    prelude::*,
+    render::{
+        render_resource::{
+            CachedPipelineState, PipelineCache, PipelineCacheError, PipelineDescriptor,
+        },
+        view::NoFrustumCulling,
+        Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+    },
+    utils::HashSet,
+};
+use std::{
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use crate::scene_ready::SceneReady;
+
+/// Compiles render pipelines up front, so the first frame with a new kind of object
+/// doesn't hitch.
+///
+/// Spawn one `ShaderWarmup` entity per material and mesh layout combination, at
+/// `warmup_transform()`. They are drawn behind the camera until the render world has
+/// prepared their materials and has no queued pipelines left, then despawned, and
+/// `ShaderWarmupDone` is set. Materials other than `StandardMaterial` need a
+/// `WarmupMaterialPlugin`.
+pub struct ShaderWarmupPlugin;
+
+impl Plugin for ShaderWarmupPlugin {
+    fn build(&self, app: &mut App) {
+        let progress = SharedWarmupProgress::default();
+        let failed_shaders = FailedShaders::default();
+        app.insert_resource(progress.clone())
+            .insert_resource(failed_shaders.clone())
+            .insert_resource(ShaderWarmupDone(false))
+            .add_plugins(WarmupMaterialPlugin::<StandardMaterial>::default())
+            .add_systems(Update, (disable_warmup_culling, finish_warmup));
+
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .insert_resource(progress)
+                .insert_resource(failed_shaders)
+                .init_resource::<WarmupProgress>()
+                .add_systems(
+                    ExtractSchedule,
+                    extract_warmup_progress.before(ExtractWarmupMaterials),
+                )
+                .add_systems(
+                    Render,
+                    (
+                        report_warmup_progress.after(CountUnpreparedMaterials),
+                        collect_failed_shaders,
+                    )
+                        .in_set(RenderSet::Cleanup),
+                );
+        }
+    }
+}
+
+/// Makes the warmup wait for materials of type `M` on `ShaderWarmup` entities to be
+/// prepared, since their pipelines are only queued after that.
+pub struct WarmupMaterialPlugin<M>(PhantomData<M>);
+
+impl<M> Default for WarmupMaterialPlugin<M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<M: Material> Plugin for WarmupMaterialPlugin<M> {
+    fn build(&self, app: &mut App) {
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<WarmupMaterials<M>>()
+                .add_systems(
+                    ExtractSchedule,
+                    extract_warmup_materials::<M>.in_set(ExtractWarmupMaterials),
+                )
+                .add_systems(
+                    Render,
+                    count_unprepared_materials::<M>
+                        .in_set(CountUnpreparedMaterials)
+                        .in_set(RenderSet::Cleanup),
+                );
+        }
+    }
+}
+
+/// Marks an entity, or a scene root, that only exists to compile its pipelines.
+#[derive(Component)]
+pub struct ShaderWarmup;
+
+#[derive(Resource)]
+pub struct ShaderWarmupDone(pub bool);
+
+/// Behind the camera, where warmup entities are queued for drawing but never seen.
+pub fn warmup_transform() -> Transform {
+    Transform::from_xyz(0.0, 0.0, 5.0)
+}
+
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+struct ExtractWarmupMaterials;
+
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+struct CountUnpreparedMaterials;
+
+/// What the warmup is still waiting for, counted in one render world frame.
+#[derive(Resource, Clone, Copy, Default, Debug)]
+struct WarmupProgress {
+    /// Every warmup entity exists, including the meshes of scenes.
+    spawned: bool,
+    unprepared_materials: usize,
+    queued_pipelines: usize,
+}
+
+/// The last `WarmupProgress` of the render world, shared with the main world.
+#[derive(Resource, Clone, Default)]
+struct SharedWarmupProgress(Arc<Mutex<Option<WarmupProgress>>>);
+
+/// Materials of type `M` on warmup entities, in the render world.
+#[derive(Resource)]
+struct WarmupMaterials<M: Material>(Vec<Handle<M>>);
+
+impl<M: Material> Default for WarmupMaterials<M> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+/// Shaders of pipelines that failed to compile, shared with the main world, so materials
+/// with a custom shader can fall back to a `StandardMaterial`.
+#[derive(Resource, Clone, Default)]
+pub struct FailedShaders(Arc<Mutex<HashSet<HandleId>>>);
+
+impl FailedShaders {
+    pub fn contains(&self, shader: &Handle<Shader>) -> bool {
+        self.0.lock().unwrap().contains(&shader.id())
+    }
+}
+
+fn extract_warmup_progress(
+    mut progress: ResMut<WarmupProgress>,
+    q_warmup: Extract<Query<(Option<&Handle<Scene>>, Option<&SceneReady>), With<ShaderWarmup>>>,
+) {
+    // the meshes of a scene only exist once it's ready
+    *progress = WarmupProgress {
+        spawned: !q_warmup.is_empty()
+            && q_warmup
+                .iter()
+                .all(|(scene, ready)| scene.is_none() || ready.is_some()),
+        ..default()
+    };
+}
+
+fn extract_warmup_materials<M: Material>(
+    mut materials: ResMut<WarmupMaterials<M>>,
+    q_warmup: Extract<Query<Entity, With<ShaderWarmup>>>,
+    q_children: Extract<Query<&Children>>,
+    q_material: Extract<Query<&Handle<M>>>,
+) {
+    materials.0.clear();
+    for root in q_warmup.iter() {
+        let entities = std::iter::once(root).chain(q_children.iter_descendants(root));
+        materials.0.extend(
+            q_material
+                .iter_many(entities)
+                .map(|material| material.clone_weak()),
+        );
+    }
+}
+
+fn count_unprepared_materials<M: Material>(
+    materials: Res<WarmupMaterials<M>>,
+    render_materials: Res<RenderMaterials<M>>,
+    mut progress: ResMut<WarmupProgress>,
+) {
+    progress.unprepared_materials += materials
+        .0
+        .iter()
+        .filter(|material| !render_materials.0.contains_key(*material))
+        .count();
+}
+
+fn report_warmup_progress(
+    pipeline_cache: Res<PipelineCache>,
+    progress: Res<WarmupProgress>,
+    shared: Res<SharedWarmupProgress>,
+) {
+    // pipelines waiting for a shader to load are retried
+    let queued_pipelines = pipeline_cache
+        .pipelines()
+        .filter(|pipeline| {
+            matches!(
+                pipeline.state,
+                CachedPipelineState::Queued
+                    | CachedPipelineState::Err(
+                        PipelineCacheError::ShaderNotLoaded(_)
+                            | PipelineCacheError::ShaderImportNotYetAvailable
+                    )
+            )
+        })
+        .count();
+    *shared.0.lock().unwrap() = Some(WarmupProgress {
+        queued_pipelines,
+        ..*progress
+    });
+}
+
+fn collect_failed_shaders(pipeline_cache: Res<PipelineCache>, failed: Res<FailedShaders>) {
+    let mut failed = failed.0.lock().unwrap();
+    for pipeline in pipeline_cache.pipelines() {
+        let (
+            CachedPipelineState::Err(error),
+            PipelineDescriptor::RenderPipelineDescriptor(descriptor),
+        ) = (&pipeline.state, &pipeline.descriptor)
+        else {
+            continue;
+        };
+        // still loading, these are retried
+        if matches!(
+            error,
+            PipelineCacheError::ShaderNotLoaded(_)
+                | PipelineCacheError::ShaderImportNotYetAvailable
+        ) {
+            continue;
+        }
+        failed.insert(descriptor.vertex.shader.id());
+        if let Some(fragment) = &descriptor.fragment {
+            failed.insert(fragment.shader.id());
+        }
+    }
+}
+
+// otherwise they would be culled, and never get their pipelines specialized
+fn disable_warmup_culling(
+    mut commands: Commands,
+    q_warmup: Query<Entity, With<ShaderWarmup>>,
+    q_children: Query<&Children>,
+    q_mesh: Query<(), (With<Handle<Mesh>>, Without<NoFrustumCulling>)>,
+) {
+    for root in q_warmup.iter() {
+        for entity in std::iter::once(root).chain(q_children.iter_descendants(root)) {
+            if q_mesh.contains(entity) {
+                commands.entity(entity).insert(NoFrustumCulling);
+            }
+        }
+    }
+}
+
+fn finish_warmup(
+    mut commands: Commands,
+    progress: Res<SharedWarmupProgress>,
+    mut done: ResMut<ShaderWarmupDone>,
+    q_warmup: Query<Entity, With<ShaderWarmup>>,
+) {
+    if done.0 || q_warmup.is_empty() {
+        return;
+    }
+
+    let Some(progress) = *progress.0.lock().unwrap() else {
+        return;
+    };
+    if !progress.spawned || progress.unprepared_materials > 0 || progress.queued_pipelines > 0 {
+        return;
+    }
+
+    info!("shader warmup finished");
+    done.0 = true;
+    for entity in q_warmup.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
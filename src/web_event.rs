@@ -1,5 +1,11 @@
 use web_sys::{CustomEvent, CustomEventInit, Window, EventTarget};
 use bevy::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::camera::SafeArea;
+
+static DOCUMENT_WAS_HIDDEN: AtomicBool = AtomicBool::new(false);
 
 fn trigger_custom_event(event_name: &str) {
     let window = web_sys::window().expect("could not get window");
@@ -16,4 +22,71 @@ fn trigger_custom_event(event_name: &str) {
 pub fn send_loaded_event() {
     info!("Sending AssetsLoaded event");
     trigger_custom_event("AssetsLoaded");
-}
\ No newline at end of file
+}
+
+pub fn listen_visibility_change() {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .expect("could not get document");
+
+    // frames stop while the tab is hidden, so remember it until the game gets to check
+    let on_visibility_change = Closure::<dyn FnMut()>::new(|| {
+        let hidden = web_sys::window()
+            .and_then(|window| window.document())
+            .map(|document| document.hidden())
+            .unwrap_or(false);
+        if hidden {
+            DOCUMENT_WAS_HIDDEN.store(true, Ordering::Relaxed);
+        }
+    });
+
+    let target: &EventTarget = document.as_ref();
+    target
+        .add_event_listener_with_callback(
+            "visibilitychange",
+            on_visibility_change.as_ref().unchecked_ref(),
+        )
+        .expect("Could not listen to visibilitychange");
+    on_visibility_change.forget();
+}
+
+pub fn take_document_was_hidden() -> bool {
+    DOCUMENT_WAS_HIDDEN.swap(false, Ordering::Relaxed)
+}
+
+/// The browser's `env(safe-area-inset-*)`, in CSS pixels. Only non-zero with
+/// `viewport-fit=cover` in the viewport meta tag, see index.html.
+pub fn safe_area_insets() -> Option<SafeArea> {
+    let window = web_sys::window()?;
+    let document = window.document()?;
+    let body = document.body()?;
+
+    // env() can't be read from script, so measure an element padded by it
+    let probe = document.create_element("div").ok()?;
+    probe
+        .set_attribute(
+            "style",
+            "position: fixed; visibility: hidden; pointer-events: none; \
+             padding: env(safe-area-inset-top) env(safe-area-inset-right) \
+             env(safe-area-inset-bottom) env(safe-area-inset-left);",
+        )
+        .ok()?;
+    body.append_child(&probe).ok()?;
+    let style = window.get_computed_style(&probe).ok().flatten();
+    probe.remove();
+    let style = style?;
+
+    let inset = |property: &str| {
+        style
+            .get_property_value(property)
+            .ok()
+            .and_then(|value| value.trim_end_matches("px").parse::<f32>().ok())
+            .unwrap_or(0.0)
+    };
+    Some(SafeArea {
+        left: inset("padding-left"),
+        right: inset("padding-right"),
+        top: inset("padding-top"),
+        bottom: inset("padding-bottom"),
+    })
+}
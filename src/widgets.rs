@@ -0,0 +1,955 @@
+use bevy::{ecs::system::EntityCommands, prelude::*, ui::FocusPolicy, window::PrimaryWindow};
+
+use crate::{
+    button::{button_bundle, ButtonCommands, ButtonDisabled, ButtonSize, InactiveButton},
+    focus::{FocusActivated, FocusAdjustable, FocusAdjusted, FocusTrap, Focusable, Focused},
+    theme::{ThemedBackground, UiTheme},
+};
+
+pub struct WidgetsPlugin;
+
+impl Plugin for WidgetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ModalClosed>().add_systems(
+            Update,
+            (
+                drag_slider,
+                adjust_widget,
+                activate_widget,
+                update_slider_fill,
+                update_toggle_knob,
+                update_checkbox_mark,
+                update_dropdown_text,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// A horizontal slider with a value in 0..=1.
+#[derive(Component)]
+pub struct Slider {
+    pub value: f32,
+    /// How much left/right on a keyboard or gamepad changes the value.
+    pub step: f32,
+}
+
+#[derive(Component)]
+struct SliderFill;
+
+#[derive(Component)]
+pub struct Toggle {
+    pub on: bool,
+}
+
+#[derive(Component)]
+struct ToggleKnob;
+
+/// A button showing the selected option, which opens a list of all options when activated.
+#[derive(Component)]
+pub struct Dropdown {
+    pub options: Vec<String>,
+    pub selected: usize,
+}
+
+#[derive(Component)]
+struct DropdownText;
+
+#[derive(Component)]
+struct DropdownList {
+    dropdown: Entity,
+}
+
+#[derive(Component)]
+struct DropdownOption {
+    dropdown: Entity,
+    index: usize,
+}
+
+/// Sent when one of a modal dialog's buttons is activated. The dialog despawns itself.
+#[derive(Event)]
+pub struct ModalClosed {
+    pub modal: Entity,
+    /// Index of the activated button, in the order they were added to the builder.
+    pub button: usize,
+}
+
+#[derive(Component)]
+pub struct Checkbox {
+    pub checked: bool,
+}
+
+#[derive(Component)]
+struct CheckboxMark;
+
+#[derive(Component)]
+pub struct Modal;
+
+#[derive(Component)]
+struct ModalButton {
+    modal: Entity,
+    index: usize,
+}
+
+fn widget_text(theme: &UiTheme, text: &str, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        text,
+        TextStyle {
+            font_size,
+            color: theme.text,
+            ..default()
+        },
+    )
+}
+
+fn toggle_knob_color(theme: &UiTheme, on: bool) -> Color {
+    if on {
+        theme.text
+    } else {
+        theme.text.with_a(0.4)
+    }
+}
+
+fn toggle_justify_content(on: bool) -> JustifyContent {
+    if on {
+        JustifyContent::FlexEnd
+    } else {
+        JustifyContent::FlexStart
+    }
+}
+
+fn checkbox_mark_display(checked: bool) -> Display {
+    if checked {
+        Display::Flex
+    } else {
+        Display::None
+    }
+}
+
+fn widget_button_bundle(theme: &UiTheme, style: Style) -> ButtonBundle {
+    ButtonBundle {
+        style: Style {
+            border: UiRect::all(Val::Px(1.0)),
+            ..style
+        },
+        border_color: theme.border.into(),
+        background_color: theme.button.into(),
+        ..default()
+    }
+}
+
+pub struct SliderBuilder<'w, 's, 'a> {
+    commands: &'a mut Commands<'w, 's>,
+    theme: UiTheme,
+    value: f32,
+    step: f32,
+}
+
+impl<'w, 's, 'a> SliderBuilder<'w, 's, 'a> {
+    pub fn step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
+
+    pub fn spawn(self) -> EntityCommands<'w, 's, 'a> {
+        let SliderBuilder {
+            commands,
+            theme,
+            value,
+            step,
+        } = self;
+        let value = value.clamp(0.0, 1.0);
+        let mut e = commands.spawn_empty();
+
+        e.insert((
+            widget_button_bundle(
+                &theme,
+                Style {
+                    width: Val::Px(200.0),
+                    height: Val::Px(24.0),
+                    ..default()
+                },
+            ),
+            Slider { value, step },
+            Focusable::default(),
+            FocusAdjustable,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                SliderFill,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(value * 100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: theme.text.with_a(0.5).into(),
+                    ..default()
+                },
+            ));
+        });
+        e
+    }
+}
+
+pub struct ToggleBuilder<'w, 's, 'a> {
+    commands: &'a mut Commands<'w, 's>,
+    theme: UiTheme,
+    on: bool,
+    disabled: bool,
+}
+
+impl<'w, 's, 'a> ToggleBuilder<'w, 's, 'a> {
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    pub fn spawn(self) -> EntityCommands<'w, 's, 'a> {
+        let ToggleBuilder {
+            commands,
+            theme,
+            on,
+            disabled,
+        } = self;
+        let mut e = commands.spawn_empty();
+
+        e.insert((
+            widget_button_bundle(
+                &theme,
+                Style {
+                    width: Val::Px(60.0),
+                    height: Val::Px(30.0),
+                    padding: UiRect::all(Val::Px(3.0)),
+                    justify_content: toggle_justify_content(on),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+            ),
+            Toggle { on },
+            Focusable::default(),
+            FocusAdjustable,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ToggleKnob,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(22.0),
+                        height: Val::Px(22.0),
+                        ..default()
+                    },
+                    background_color: toggle_knob_color(&theme, on).into(),
+                    ..default()
+                },
+            ));
+        });
+        if disabled {
+            e.insert(ButtonDisabled);
+        }
+        e
+    }
+}
+
+pub struct CheckboxBuilder<'w, 's, 'a> {
+    commands: &'a mut Commands<'w, 's>,
+    theme: UiTheme,
+    checked: bool,
+}
+
+impl<'w, 's, 'a> CheckboxBuilder<'w, 's, 'a> {
+    pub fn spawn(self) -> EntityCommands<'w, 's, 'a> {
+        let CheckboxBuilder {
+            commands,
+            theme,
+            checked,
+        } = self;
+        let mut e = commands.spawn_empty();
+
+        e.insert((
+            widget_button_bundle(
+                &theme,
+                Style {
+                    width: Val::Px(30.0),
+                    height: Val::Px(30.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+            ),
+            Checkbox { checked },
+            Focusable::default(),
+            FocusAdjustable,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                CheckboxMark,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(16.0),
+                        height: Val::Px(16.0),
+                        display: checkbox_mark_display(checked),
+                        ..default()
+                    },
+                    background_color: theme.text.into(),
+                    ..default()
+                },
+            ));
+        });
+        e
+    }
+}
+
+pub struct DropdownBuilder<'w, 's, 'a> {
+    commands: &'a mut Commands<'w, 's>,
+    theme: UiTheme,
+    options: Vec<String>,
+    selected: usize,
+}
+
+impl<'w, 's, 'a> DropdownBuilder<'w, 's, 'a> {
+    pub fn selected(mut self, selected: usize) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    pub fn spawn(self) -> EntityCommands<'w, 's, 'a> {
+        let DropdownBuilder {
+            commands,
+            theme,
+            options,
+            selected,
+        } = self;
+        let selected = selected.min(options.len().saturating_sub(1));
+        let text = options.get(selected).cloned().unwrap_or_default();
+        let mut e = commands.spawn_empty();
+
+        e.insert((
+            widget_button_bundle(
+                &theme,
+                Style {
+                    width: Val::Px(200.0),
+                    height: Val::Px(40.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+            ),
+            Dropdown { options, selected },
+            Focusable::default(),
+            FocusAdjustable,
+        ))
+        .with_children(|parent| {
+            parent.spawn((DropdownText, widget_text(&theme, &text, 24.0)));
+        });
+        e
+    }
+}
+
+pub struct PanelBuilder<'w, 's, 'a> {
+    commands: &'a mut Commands<'w, 's>,
+    theme: UiTheme,
+    width: Val,
+}
+
+impl<'w, 's, 'a> PanelBuilder<'w, 's, 'a> {
+    pub fn width(mut self, width: Val) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn spawn(self) -> EntityCommands<'w, 's, 'a> {
+        let PanelBuilder {
+            commands,
+            theme,
+            width,
+        } = self;
+
+        commands.spawn((
+            ThemedBackground::Panel,
+            NodeBundle {
+                style: Style {
+                    width,
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(20.0)),
+                    border: UiRect::all(Val::Px(1.0)),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                border_color: theme.border.into(),
+                background_color: theme.panel.into(),
+                ..default()
+            },
+        ))
+    }
+}
+
+pub struct ModalBuilder<'w, 's, 'a> {
+    commands: &'a mut Commands<'w, 's>,
+    theme: UiTheme,
+    title: String,
+    message: Option<String>,
+    buttons: Vec<String>,
+}
+
+impl<'w, 's, 'a> ModalBuilder<'w, 's, 'a> {
+    pub fn message(mut self, message: &str) -> Self {
+        self.message = Some(message.to_owned());
+        self
+    }
+
+    pub fn button(mut self, label: &str) -> Self {
+        self.buttons.push(label.to_owned());
+        self
+    }
+
+    pub fn spawn(self) -> EntityCommands<'w, 's, 'a> {
+        let ModalBuilder {
+            commands,
+            theme,
+            title,
+            message,
+            buttons,
+        } = self;
+        let modal = commands.spawn_empty().id();
+
+        let buttons = buttons
+            .iter()
+            .enumerate()
+            .map(|(index, label)| {
+                commands
+                    .text_button(&theme, label)
+                    .size(ButtonSize::Small)
+                    .spawn()
+                    .insert((
+                        ModalButton { modal, index },
+                        Focusable {
+                            order: index as i32,
+                        },
+                    ))
+                    .id()
+            })
+            .collect::<Vec<_>>();
+
+        let button_row = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    margin: UiRect::top(Val::Px(20.0)),
+                    column_gap: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .push_children(&buttons)
+            .id();
+
+        let panel = commands
+            .panel(&theme)
+            .width(Val::Px(420.0))
+            .spawn()
+            .with_children(|parent| {
+                parent.spawn(widget_text(&theme, &title, 36.0));
+                if let Some(message) = &message {
+                    parent.spawn(widget_text(&theme, message, 24.0).with_style(Style {
+                        margin: UiRect::top(Val::Px(10.0)),
+                        ..default()
+                    }));
+                }
+            })
+            .add_child(button_row)
+            .id();
+
+        let mut e = commands.entity(modal);
+        e.insert((
+            Modal,
+            FocusTrap,
+            ThemedBackground::Overlay,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: theme.overlay.into(),
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(40),
+                ..default()
+            },
+        ))
+        .add_child(panel);
+        e
+    }
+}
+
+pub trait WidgetCommands<'w, 's> {
+    fn slider<'a>(&'a mut self, theme: &UiTheme, value: f32) -> SliderBuilder<'w, 's, 'a>;
+    fn toggle<'a>(&'a mut self, theme: &UiTheme, on: bool) -> ToggleBuilder<'w, 's, 'a>;
+    fn checkbox<'a>(&'a mut self, theme: &UiTheme, checked: bool) -> CheckboxBuilder<'w, 's, 'a>;
+    fn dropdown<'a>(&'a mut self, theme: &UiTheme, options: &[&str])
+        -> DropdownBuilder<'w, 's, 'a>;
+    fn panel<'a>(&'a mut self, theme: &UiTheme) -> PanelBuilder<'w, 's, 'a>;
+    fn modal<'a>(&'a mut self, theme: &UiTheme, title: &str) -> ModalBuilder<'w, 's, 'a>;
+}
+
+impl<'w, 's> WidgetCommands<'w, 's> for Commands<'w, 's> {
+    fn slider<'a>(&'a mut self, theme: &UiTheme, value: f32) -> SliderBuilder<'w, 's, 'a> {
+        SliderBuilder {
+            commands: self,
+            theme: *theme,
+            value,
+            step: 0.1,
+        }
+    }
+
+    fn toggle<'a>(&'a mut self, theme: &UiTheme, on: bool) -> ToggleBuilder<'w, 's, 'a> {
+        ToggleBuilder {
+            commands: self,
+            theme: *theme,
+            on,
+            disabled: false,
+        }
+    }
+
+    fn checkbox<'a>(&'a mut self, theme: &UiTheme, checked: bool) -> CheckboxBuilder<'w, 's, 'a> {
+        CheckboxBuilder {
+            commands: self,
+            theme: *theme,
+            checked,
+        }
+    }
+
+    fn dropdown<'a>(
+        &'a mut self,
+        theme: &UiTheme,
+        options: &[&str],
+    ) -> DropdownBuilder<'w, 's, 'a> {
+        DropdownBuilder {
+            commands: self,
+            theme: *theme,
+            options: options.iter().map(|option| option.to_string()).collect(),
+            selected: 0,
+        }
+    }
+
+    fn panel<'a>(&'a mut self, theme: &UiTheme) -> PanelBuilder<'w, 's, 'a> {
+        PanelBuilder {
+            commands: self,
+            theme: *theme,
+            width: Val::Auto,
+        }
+    }
+
+    fn modal<'a>(&'a mut self, theme: &UiTheme, title: &str) -> ModalBuilder<'w, 's, 'a> {
+        ModalBuilder {
+            commands: self,
+            theme: *theme,
+            title: title.to_owned(),
+            message: None,
+            buttons: vec![],
+        }
+    }
+}
+
+fn drag_slider(
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_slider: Query<(Entity, &Interaction, &Node, &GlobalTransform, &mut Slider)>,
+    q_inactive: Query<(), InactiveButton>,
+) {
+    let Some(cursor_position) = q_window.get_single().ok().and_then(|w| w.cursor_position()) else {
+        return;
+    };
+
+    for (entity, interaction, node, global_transform, mut slider) in q_slider.iter_mut() {
+        if *interaction != Interaction::Pressed || q_inactive.contains(entity) {
+            continue;
+        }
+
+        // ui nodes are positioned by their center
+        let width = node.size().x.max(1.0);
+        let left = global_transform.translation().x - width / 2.0;
+        let value = ((cursor_position.x - left) / width).clamp(0.0, 1.0);
+        if slider.value != value {
+            slider.value = value;
+        }
+    }
+}
+
+fn adjust_widget(
+    mut adjusted: EventReader<FocusAdjusted>,
+    mut q_slider: Query<&mut Slider>,
+    mut q_toggle: Query<&mut Toggle>,
+    mut q_checkbox: Query<&mut Checkbox>,
+    mut q_dropdown: Query<&mut Dropdown>,
+) {
+    for FocusAdjusted(entity, direction) in adjusted.iter() {
+        if let Ok(mut slider) = q_slider.get_mut(*entity) {
+            slider.value = (slider.value + slider.step * *direction as f32).clamp(0.0, 1.0);
+        }
+        if let Ok(mut toggle) = q_toggle.get_mut(*entity) {
+            toggle.on = *direction > 0;
+        }
+        if let Ok(mut checkbox) = q_checkbox.get_mut(*entity) {
+            checkbox.checked = *direction > 0;
+        }
+        if let Ok(mut dropdown) = q_dropdown.get_mut(*entity) {
+            let len = dropdown.options.len().max(1) as i32;
+            dropdown.selected = (dropdown.selected as i32 + direction).rem_euclid(len) as usize;
+        }
+    }
+}
+
+fn activate_widget(
+    mut commands: Commands,
+    mut activated: EventReader<FocusActivated>,
+    mut q_toggle: Query<&mut Toggle>,
+    mut q_checkbox: Query<&mut Checkbox>,
+    mut q_dropdown: Query<&mut Dropdown>,
+    q_dropdown_option: Query<&DropdownOption>,
+    q_dropdown_list: Query<(Entity, &DropdownList)>,
+    q_modal_button: Query<&ModalButton>,
+    mut modal_closed: EventWriter<ModalClosed>,
+    mut focused: ResMut<Focused>,
+    theme: Res<UiTheme>,
+) {
+    for FocusActivated(entity) in activated.iter() {
+        if let Ok(mut toggle) = q_toggle.get_mut(*entity) {
+            toggle.on = !toggle.on;
+        }
+
+        if let Ok(mut checkbox) = q_checkbox.get_mut(*entity) {
+            checkbox.checked = !checkbox.checked;
+        }
+
+        if let Ok(modal_button) = q_modal_button.get(*entity) {
+            modal_closed.send(ModalClosed {
+                modal: modal_button.modal,
+                button: modal_button.index,
+            });
+            commands.entity(modal_button.modal).despawn_recursive();
+        }
+
+        if let Ok(option) = q_dropdown_option.get(*entity) {
+            if let Ok(mut dropdown) = q_dropdown.get_mut(option.dropdown) {
+                dropdown.selected = option.index;
+            }
+            for (list, _) in q_dropdown_list
+                .iter()
+                .filter(|(_, list)| list.dropdown == option.dropdown)
+            {
+                commands.entity(list).despawn_recursive();
+            }
+            focused.0 = Some(option.dropdown);
+        }
+
+        if let Ok(dropdown) = q_dropdown.get(*entity) {
+            let open_list = q_dropdown_list
+                .iter()
+                .find(|(_, list)| list.dropdown == *entity);
+            if let Some((list, _)) = open_list {
+                commands.entity(list).despawn_recursive();
+                continue;
+            }
+
+            let options = dropdown
+                .options
+                .iter()
+                .enumerate()
+                .map(|(index, option)| {
+                    commands
+                        .spawn((
+                            button_bundle(
+                                &theme,
+                                Style {
+                                    width: Val::Px(200.0),
+                                    height: Val::Px(40.0),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                            ),
+                            DropdownOption {
+                                dropdown: *entity,
+                                index,
+                            },
+                            Focusable {
+                                order: index as i32,
+                            },
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(widget_text(&theme, option, 24.0));
+                        })
+                        .id()
+                })
+                .collect::<Vec<_>>();
+            focused.0 = options.get(dropdown.selected).copied();
+
+            let list = commands
+                .spawn((
+                    DropdownList { dropdown: *entity },
+                    FocusTrap,
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            top: Val::Percent(100.0),
+                            left: Val::Px(0.0),
+                            flex_direction: FlexDirection::Column,
+                            ..default()
+                        },
+                        z_index: ZIndex::Global(30),
+                        ..default()
+                    },
+                ))
+                .push_children(&options)
+                .id();
+            commands.entity(*entity).add_child(list);
+        }
+    }
+}
+
+fn update_slider_fill(
+    q_slider: Query<(&Slider, &Children), Changed<Slider>>,
+    mut q_fill: Query<&mut Style, With<SliderFill>>,
+) {
+    for (slider, children) in q_slider.iter() {
+        for child in children.iter() {
+            if let Ok(mut style) = q_fill.get_mut(*child) {
+                style.width = Val::Percent(slider.value * 100.0);
+            }
+        }
+    }
+}
+
+fn update_toggle_knob(
+    mut q_toggle: Query<(&Toggle, &mut Style, &Children), Changed<Toggle>>,
+    mut q_knob: Query<&mut BackgroundColor, With<ToggleKnob>>,
+    theme: Res<UiTheme>,
+) {
+    for (toggle, mut style, children) in q_toggle.iter_mut() {
+        style.justify_content = toggle_justify_content(toggle.on);
+        for child in children.iter() {
+            if let Ok(mut background_color) = q_knob.get_mut(*child) {
+                background_color.0 = toggle_knob_color(&theme, toggle.on);
+            }
+        }
+    }
+}
+
+fn update_checkbox_mark(
+    q_checkbox: Query<(&Checkbox, &Children), Changed<Checkbox>>,
+    mut q_mark: Query<&mut Style, With<CheckboxMark>>,
+) {
+    for (checkbox, children) in q_checkbox.iter() {
+        for child in children.iter() {
+            if let Ok(mut style) = q_mark.get_mut(*child) {
+                style.display = checkbox_mark_display(checkbox.checked);
+            }
+        }
+    }
+}
+
+fn update_dropdown_text(
+    q_dropdown: Query<(&Dropdown, &Children), Changed<Dropdown>>,
+    mut q_text: Query<&mut Text, With<DropdownText>>,
+) {
+    for (dropdown, children) in q_dropdown.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = q_text.get_mut(*child) {
+                if let Some(option) = dropdown.options.get(dropdown.selected) {
+                    text.sections[0].value = option.clone();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{asset::HandleId, ecs::system::CommandQueue};
+
+    /// Spawns a widget through `Commands`, the way a system would.
+    fn spawn_widget(
+        world: &mut World,
+        spawn: impl FnOnce(&mut Commands, &UiTheme) -> Entity,
+    ) -> Entity {
+        let theme = UiTheme::from_world(world);
+        let mut queue = CommandQueue::default();
+        let entity = spawn(&mut Commands::new(&mut queue, world), &theme);
+        queue.apply(world);
+        entity
+    }
+
+    fn children(world: &World, entity: Entity) -> Vec<Entity> {
+        world
+            .get::<Children>(entity)
+            .map_or(vec![], |children| children.to_vec())
+    }
+
+    fn text(world: &World, entity: Entity) -> &str {
+        &world.get::<Text>(entity).unwrap().sections[0].value
+    }
+
+    fn assert_focusable_button(world: &World, entity: Entity) {
+        assert!(world.get::<Button>(entity).is_some());
+        assert!(world.get::<Focusable>(entity).is_some());
+    }
+
+    #[test]
+    fn text_button_has_text_and_shadow() {
+        let mut world = World::new();
+        let button = spawn_widget(&mut world, |commands, theme| {
+            commands.text_button(theme, "Play").spawn().id()
+        });
+
+        assert_focusable_button(&world, button);
+        assert!(world.get::<ButtonDisabled>(button).is_none());
+        let texts = children(&world, button);
+        assert_eq!(texts.len(), 2);
+        for entity in texts {
+            assert_eq!(text(&world, entity), "Play");
+        }
+    }
+
+    #[test]
+    fn icon_button_has_icon() {
+        let mut world = World::new();
+        let icon = Handle::<Image>::weak(HandleId::random::<Image>());
+        let button = spawn_widget(&mut world, |commands, theme| {
+            commands.icon_button(theme, icon.clone()).spawn().id()
+        });
+
+        assert_focusable_button(&world, button);
+        let [image] = children(&world, button)[..] else {
+            panic!("expected one child");
+        };
+        assert_eq!(world.get::<UiImage>(image).unwrap().texture, icon);
+    }
+
+    #[test]
+    fn slider_has_fill() {
+        let mut world = World::new();
+        let slider = spawn_widget(&mut world, |commands, theme| {
+            commands.slider(theme, 1.5).step(0.25).spawn().id()
+        });
+
+        assert_focusable_button(&world, slider);
+        assert!(world.get::<FocusAdjustable>(slider).is_some());
+        let state = world.get::<Slider>(slider).unwrap();
+        assert_eq!((state.value, state.step), (1.0, 0.25));
+        let [fill] = children(&world, slider)[..] else {
+            panic!("expected one child");
+        };
+        assert!(world.get::<SliderFill>(fill).is_some());
+        assert_eq!(world.get::<Style>(fill).unwrap().width, Val::Percent(100.0));
+    }
+
+    #[test]
+    fn toggle_has_knob_and_can_be_disabled() {
+        let mut world = World::new();
+        let toggle = spawn_widget(&mut world, |commands, theme| {
+            commands.toggle(theme, true).disabled(true).spawn().id()
+        });
+
+        assert_focusable_button(&world, toggle);
+        assert!(world.get::<ButtonDisabled>(toggle).is_some());
+        assert!(world.get::<Toggle>(toggle).unwrap().on);
+        assert_eq!(
+            world.get::<Style>(toggle).unwrap().justify_content,
+            JustifyContent::FlexEnd
+        );
+        let [knob] = children(&world, toggle)[..] else {
+            panic!("expected one child");
+        };
+        assert!(world.get::<ToggleKnob>(knob).is_some());
+    }
+
+    #[test]
+    fn checkbox_hides_mark_when_unchecked() {
+        let mut world = World::new();
+        let checkbox = spawn_widget(&mut world, |commands, theme| {
+            commands.checkbox(theme, false).spawn().id()
+        });
+
+        assert_focusable_button(&world, checkbox);
+        assert!(!world.get::<Checkbox>(checkbox).unwrap().checked);
+        let [mark] = children(&world, checkbox)[..] else {
+            panic!("expected one child");
+        };
+        assert!(world.get::<CheckboxMark>(mark).is_some());
+        assert_eq!(world.get::<Style>(mark).unwrap().display, Display::None);
+    }
+
+    #[test]
+    fn dropdown_shows_selected_option() {
+        let mut world = World::new();
+        let dropdown = spawn_widget(&mut world, |commands, theme| {
+            commands
+                .dropdown(theme, &["Low", "Medium", "High"])
+                .selected(5)
+                .spawn()
+                .id()
+        });
+
+        assert_focusable_button(&world, dropdown);
+        assert_eq!(world.get::<Dropdown>(dropdown).unwrap().selected, 2);
+        let [label] = children(&world, dropdown)[..] else {
+            panic!("expected one child");
+        };
+        assert!(world.get::<DropdownText>(label).is_some());
+        assert_eq!(text(&world, label), "High");
+    }
+
+    #[test]
+    fn panel_is_themed() {
+        let mut world = World::new();
+        let panel = spawn_widget(&mut world, |commands, theme| {
+            commands.panel(theme).width(Val::Px(300.0)).spawn().id()
+        });
+
+        assert!(matches!(
+            world.get::<ThemedBackground>(panel),
+            Some(ThemedBackground::Panel)
+        ));
+        let style = world.get::<Style>(panel).unwrap();
+        assert_eq!(style.width, Val::Px(300.0));
+        assert_eq!(style.flex_direction, FlexDirection::Column);
+    }
+
+    #[test]
+    fn modal_has_panel_with_texts_and_buttons() {
+        let mut world = World::new();
+        let modal = spawn_widget(&mut world, |commands, theme| {
+            commands
+                .modal(theme, "Quit?")
+                .message("Sure?")
+                .button("No")
+                .button("Yes")
+                .spawn()
+                .id()
+        });
+
+        assert!(world.get::<Modal>(modal).is_some());
+        assert!(world.get::<FocusTrap>(modal).is_some());
+        let [panel] = children(&world, modal)[..] else {
+            panic!("expected one child");
+        };
+        assert!(world.get::<ThemedBackground>(panel).is_some());
+
+        let [title, message, button_row] = children(&world, panel)[..] else {
+            panic!("expected title, message and buttons");
+        };
+        assert_eq!(text(&world, title), "Quit?");
+        assert_eq!(text(&world, message), "Sure?");
+
+        let buttons = children(&world, button_row);
+        assert_eq!(buttons.len(), 2);
+        for (index, button) in buttons.into_iter().enumerate() {
+            assert_focusable_button(&world, button);
+            let modal_button = world.get::<ModalButton>(button).unwrap();
+            assert_eq!((modal_button.modal, modal_button.index), (modal, index));
+        }
+    }
+}